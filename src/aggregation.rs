@@ -0,0 +1,129 @@
+use cosmwasm_std::{Decimal, Order, StdResult, Storage, Uint128};
+
+use crate::state::{Aggregation, AggregationMethod, PriceData, SOURCE_PRICES};
+
+/// ## Description
+/// Combines the fresh source prices of a symbol according to its [`Aggregation`] policy.
+/// Returns [`None`] if fewer than `min_sources` fresh sources are available.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **symbol** is the symbol of the asset to aggregate the price for.
+///
+/// - **aggregation** is an object of type [`Aggregation`] which is the policy to apply.
+///
+/// - **now** is the current block time in seconds.
+pub fn aggregate_price(
+    storage: &dyn Storage,
+    symbol: &str,
+    aggregation: &Aggregation,
+    now: u64,
+) -> StdResult<Option<PriceData>> {
+    let mut sources: Vec<(String, PriceData)> = SOURCE_PRICES
+        .prefix(symbol)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .filter(|(_, price)| {
+            now.saturating_sub(price.bandchain_resolve_time) <= aggregation.max_age
+        })
+        .collect();
+
+    if (sources.len() as u64) < aggregation.min_sources {
+        return Ok(None);
+    }
+
+    // The most recently resolved source provides the BandChain request details of the aggregate
+    let latest = match sources
+        .iter()
+        .max_by_key(|(_, price)| price.bandchain_resolve_time)
+    {
+        Some((_, price)) => price.clone(),
+        None => return Ok(None),
+    };
+
+    let rate = match &aggregation.method {
+        AggregationMethod::Median => {
+            sources.sort_by_key(|(_, price)| price.rate);
+            let mid = sources.len() / 2;
+            if sources.len() % 2 == 0 {
+                (sources[mid - 1].1.rate + sources[mid].1.rate) / Uint128::from(2u128)
+            } else {
+                sources[mid].1.rate
+            }
+        }
+        AggregationMethod::Mean => {
+            let sum: Decimal = sources.iter().map(|(_, price)| price.rate).sum();
+            sum / Uint128::from(sources.len() as u128)
+        }
+        AggregationMethod::PrimaryWithFallback { primary_job_id } => {
+            match sources.iter().find(|(job_id, _)| job_id == primary_job_id) {
+                Some((_, primary)) => return Ok(Some(primary.clone())),
+                None => return Ok(Some(latest)),
+            }
+        }
+    };
+
+    Ok(Some(PriceData { rate, ..latest }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    fn save_source(storage: &mut dyn Storage, job_id: &str, rate: u64, resolve_time: u64) {
+        SOURCE_PRICES
+            .save(
+                storage,
+                ("BTC", job_id),
+                &PriceData {
+                    rate: Decimal::from_ratio(rate, 1u64),
+                    bandchain_request_id: resolve_time,
+                    bandchain_resolve_time: resolve_time,
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn aggregate_test() {
+        let mut storage = MockStorage::new();
+        save_source(&mut storage, "tvl-1", 100, 1000);
+        save_source(&mut storage, "tvl-2", 104, 1010);
+        save_source(&mut storage, "tvl-3", 101, 1005);
+        save_source(&mut storage, "tvl-4", 500, 100);
+
+        let mut aggregation = Aggregation {
+            method: AggregationMethod::Median,
+            min_sources: 3,
+            max_age: 60,
+        };
+        let price = aggregate_price(&storage, "BTC", &aggregation, 1020)
+            .unwrap()
+            .unwrap();
+        assert_eq!(price.rate, Decimal::from_ratio(101u64, 1u64));
+        assert_eq!(price.bandchain_resolve_time, 1010);
+
+        aggregation.method = AggregationMethod::Mean;
+        let price = aggregate_price(&storage, "BTC", &aggregation, 1020)
+            .unwrap()
+            .unwrap();
+        assert_eq!(price.rate, Decimal::from_ratio(305u64, 3u64));
+
+        aggregation.method = AggregationMethod::PrimaryWithFallback {
+            primary_job_id: "tvl-4".to_string(),
+        };
+        let price = aggregate_price(&storage, "BTC", &aggregation, 1020)
+            .unwrap()
+            .unwrap();
+        assert_eq!(price.rate, Decimal::from_ratio(104u64, 1u64));
+
+        aggregation.min_sources = 4;
+        assert_eq!(
+            aggregate_price(&storage, "BTC", &aggregation, 1020).unwrap(),
+            None
+        );
+    }
+}
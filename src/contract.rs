@@ -1,21 +1,76 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, coins, to_binary, Binary, Deps, DepsMut, Env, IbcMsg, IbcTimeout, MessageInfo, Response,
-    StdResult,
+    attr, coins, from_binary, to_binary, Addr, BankMsg, Binary, Coin, ContractInfoResponse,
+    CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, Event, IbcMsg, IbcTimeout, MessageInfo, Order,
+    QueryRequest, Reply, Response, StdError, StdResult, Storage, SubMsgResult, Uint128, WasmQuery,
 };
 use cw2::set_contract_version;
+use cw20::{Cw20CoinVerified, Cw20ReceiveMsg};
+use cw_storage_plus::Bound;
+use std::collections::BTreeMap;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, OracleRequestPacket, QueryMsg};
-use crate::obi::PriceDataInput;
-use crate::state::{Config, ConfigResponse, Job, PriceData, CONFIG, JOBS, JOB_COUNT, PRICES};
+use crate::history::{record_request_outcome, record_request_sent};
+use crate::ibc::{
+    store_job_prices, subscriber_callbacks, REQUESTER_CALLBACK_REPLY_ID,
+    SUBSCRIBER_CALLBACK_REPLY_ID,
+};
+use crate::json::JsonValue;
+use crate::msg::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, OracleRequestPacket, PriceRequest,
+    QueryMsg, SudoMsg,
+};
+use crate::obi::{is_price_output, ObiSchema, PriceDataInput, VrfInput};
+use crate::payment::{nonpayable, refund_msg};
+use crate::state::{
+    Aggregation, AggregationMethod, BandchainRequest, BatchRequest, CalldataVariant, Config,
+    ConfigResponse, DefaultRequest, DueJobResponse, Job, JobHistoryEntry, JobResult,
+    JobStatsResponse, JobStatus, KeeperRewards, KeeperRewardsResponse, OnDemandRequest,
+    OracleScript, OracleScriptKind, OracleScriptResponse, Payment, PendingRequest, PriceOverride,
+    PriceResponse, PriceSource, ProvenanceResponse, Randomness, RandomnessStatus,
+    RawResultResponse, RequestOutcome, SourcePriceResponse, SourceProvenance, SymbolFailures,
+    SymbolRequest, TreasuryResponse, VrfRequest, AGGREGATIONS, ALLOWED_REQUESTERS, ALLOWED_SYMBOLS,
+    BANDCHAIN_REQUESTS, BATCH_REQUESTS, CONFIG, CW20_TREASURY, CW20_UPDATE_PRICES, DUE_JOBS, JOBS,
+    JOB_COUNT, JOB_HISTORY, JOB_RESULTS, JOB_STATS, JOB_STATUS, KEEPER_REWARDS, ON_DEMAND_REQUESTS,
+    ORACLE_SCRIPTS, PENDING_REQUESTS, PRICES, PRICE_OVERRIDES, PRICE_SOURCES, RANDOMNESS,
+    RAW_RESULTS, REQUEST_COUNT, REWARD_POOL, SOURCE_PRICES, SUBSCRIPTIONS, SYMBOL_FAILURES,
+    SYMBOL_LAST_REQUEST, SYMBOL_REQUESTS, TREASURY,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:band-ibc";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const JOB_ID_PREFIX: &str = "tvl";
+const REQUEST_ID_PREFIX: &str = "req";
+const BATCH_ID_PREFIX: &str = "batch";
+const SYMBOL_REQUEST_ID_PREFIX: &str = "sym";
+const VRF_ID_PREFIX: &str = "vrf";
+
+// source ID the prices of symbol requests are stored under, which never collides with a job ID
+pub const SYMBOL_REQUEST_SOURCE: &str = "sym";
+
+// oracle script ID, multiplier, ask count and min count of the jobs merged into one request
+type RequestParams = (u64, u64, u64, u64);
+
+const FEE_LIMIT: u128 = 1000000;
+const FEE_DENOM: &str = "uband";
+const PREPARE_GAS: u64 = 100000;
+const EXECUTE_GAS: u64 = 4000000;
+const REQUEST_TIMEOUT: u64 = 300;
+const PENDING_REQUEST_EXPIRY: u64 = 3600;
+const MAX_FOLLOW_UP_DEPTH: u32 = 3;
+
+const MAX_OVERRIDE_DURATION: u64 = 7 * 24 * 3600;
+const SYMBOL_REQUEST_COOLDOWN: u64 = 300;
+const MAX_SUBSCRIBERS_PER_SYMBOL: usize = 10;
+
+const DEFAULT_TICK_BUDGET: u32 = 5;
+pub const DEFAULT_RESULT_RETENTION: u32 = 10;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 /// ## Description
 /// Creates a new contract with the specified parameters packed in the `msg` variable.
@@ -42,10 +97,16 @@ pub fn instantiate(
     let config = Config {
         owner: deps.api.addr_validate(&msg.owner)?,
         channel: String::new(),
+        keeper_reward: None,
+        tick_budget: None,
+        default_request: None,
+        result_retention: None,
+        vrf_request: None,
     };
 
     CONFIG.save(deps.storage, &config)?;
     JOB_COUNT.save(deps.storage, &0u64)?;
+    REQUEST_COUNT.save(deps.storage, &0u64)?;
 
     Ok(Response::new().add_attribute("method", "instantiate"))
 }
@@ -63,20 +124,174 @@ pub fn instantiate(
 /// - **msg** is an object of type [`ExecuteMsg`].
 /// ## Commands
 /// - **ExecuteMsg::SetChannel {
-///             channel
-///         }** Set the IBC channel to be used for the oracle requests.
+///   channel
+///   }** Set the IBC channel to be used for the oracle requests.
+///
+/// - **ExecuteMsg::RegisterJob {
+///   oracle_script_id,
+///   symbols,
+///   multiplier,
+///   ask_count,
+///   min_count,
+///   update_interval,
+///   calldata_variant
+///   }** Register a new oracle request job.
 ///
-/// - **ExecuteMsg::RegisterNewRequest {
-///             oracle_script_id,
-///             symbols,
-///             multiplier,
-///             ask_count,
-///             min_count
-///             }** Register a new oracle request job.
+/// - **ExecuteMsg::RegisterGenericJob {
+///   oracle_script_id,
+///   schema,
+///   calldata,
+///   ask_count,
+///   min_count,
+///   update_interval
+///   }** Register a new oracle request job for an oracle script returning arbitrary data.
 ///
 /// - **ExecuteMsg::UpdateJobData {
-///             job_id,
-///             }** Request and update oracle data for the specified request job ID.
+///   job_id,
+///   }** Request and update oracle data for the specified request job ID.
+///
+/// - **ExecuteMsg::UpdateJobsData {
+///   job_ids,
+///   merge
+///   }** Request and update oracle data for each of the specified request job IDs.
+///
+/// - **ExecuteMsg::UpdateAllDueJobs {
+///   limit,
+///   merge
+///   }** Request and update oracle data for the jobs that are due for an update.
+///
+/// - **ExecuteMsg::SetAggregation {
+///   symbol,
+///   method,
+///   min_sources,
+///   max_age
+///   }** Set the policy used to combine the prices reported by multiple jobs for a symbol.
+///
+/// - **ExecuteMsg::RemoveAggregation {
+///   symbol
+///   }** Remove the aggregation policy of a symbol.
+///
+/// - **ExecuteMsg::SetPriceSources {
+///   symbol,
+///   sources
+///   }** Set the ordered list of jobs the price of a symbol is served from.
+///
+/// - **ExecuteMsg::OverridePrice {
+///   symbol,
+///   rate,
+///   expires_at
+///   }** Pin the price of a symbol until the given expiry.
+///
+/// - **ExecuteMsg::RemovePriceOverride {
+///   symbol
+///   }** Remove the price override of a symbol.
+///
+/// - **ExecuteMsg::Subscribe {
+///   symbols
+///   }** Subscribe the calling contract to price updates of the specified symbols.
+///
+/// - **ExecuteMsg::Unsubscribe {
+///   symbols
+///   }** Unsubscribe the calling contract from price updates of the specified symbols.
+///
+/// - **ExecuteMsg::SetRequester {
+///   address,
+///   allowed
+///   }** Allow or disallow a contract to make one-off price and randomness requests.
+///
+/// - **ExecuteMsg::RequestPrices(PriceRequest {
+///   oracle_script_id,
+///   symbols,
+///   multiplier,
+///   ask_count,
+///   min_count
+///   })** Request fresh prices for the specified symbols, delivered back to the calling contract.
+///
+/// - **ExecuteMsg::SetUpdatePrice {
+///   job_id,
+///   update_price
+///   }** Set the funds callers must attach to request an update of a job.
+///
+/// - **ExecuteMsg::WithdrawFees {
+///   denom,
+///   amount,
+///   recipient
+///   }** Withdraw the update fees collected by the contract.
+///
+/// - **ExecuteMsg::SetKeeperReward {
+///   keeper_reward
+///   }** Set the reward paid to the address that triggered a successful job update.
+///
+/// - **ExecuteMsg::FundRewards {}** Fund the keeper reward pool with the attached native coins.
+///
+/// - **ExecuteMsg::ClaimRewards {}** Claim the keeper rewards earned by the caller.
+///
+/// - **ExecuteMsg::SetTickBudget {
+///   tick_budget
+///   }** Set the maximum number of due jobs requested by each chain tick.
+///
+/// - **ExecuteMsg::SetResultRetention {
+///   result_retention
+///   }** Set the number of raw results kept for each job.
+///
+/// - **ExecuteMsg::ReprocessResult {
+///   job_id,
+///   request_id
+///   }** Decode a stored raw result of a job again and republish the resulting prices.
+///
+/// - **ExecuteMsg::SetUpdateInterval {
+///   job_id,
+///   interval
+///   }** Set the target interval between successful updates of the specified job.
+///
+/// - **ExecuteMsg::SetFollowUps {
+///   job_id,
+///   follow_ups
+///   }** Set the jobs requested once an update of the specified job succeeds.
+///
+/// - **ExecuteMsg::SetOracleScript {
+///   oracle_script_id,
+///   oracle_script
+///   }** Register an oracle script along with its schema and default request parameters.
+///
+/// - **ExecuteMsg::RemoveOracleScript {
+///   oracle_script_id
+///   }** Remove an oracle script from the registry.
+///
+/// - **ExecuteMsg::SetVrfRequest {
+///   vrf_request
+///   }** Set the request parameters used to request randomness from the VRF oracle script.
+///
+/// - **ExecuteMsg::RequestRandomness {
+///   seed
+///   }** Request verifiable randomness, delivered back to the calling contract.
+///
+/// - **ExecuteMsg::SetDefaultRequest {
+///   default_request
+///   }** Set the request parameters used to update symbols that have no registered job.
+///
+/// - **ExecuteMsg::SetAllowedSymbols {
+///   symbols,
+///   allowed
+///   }** Allow or disallow symbols to be updated without a registered job.
+///
+/// - **ExecuteMsg::UpdateSymbols {
+///   symbols,
+///   oracle_script_id
+///   }** Request and update the prices of the specified symbols using the default request parameters.
+///
+/// - **ExecuteMsg::Receive(Cw20ReceiveMsg)** Receives cw20 tokens paying for the embedded oracle request.
+///
+/// - **ExecuteMsg::SetCw20UpdatePrice {
+///   token,
+///   update_price
+///   }** Set the amount of a cw20 token accepted as payment for an oracle request.
+///
+/// - **ExecuteMsg::WithdrawCw20Fees {
+///   token,
+///   amount,
+///   recipient
+///   }** Withdraw the cw20 update fees collected by the contract.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -92,6 +307,8 @@ pub fn execute(
             multiplier,
             ask_count,
             min_count,
+            update_interval,
+            calldata_variant,
         } => try_register_job(
             deps,
             info,
@@ -100,8 +317,111 @@ pub fn execute(
             multiplier,
             ask_count,
             min_count,
+            update_interval,
+            calldata_variant.unwrap_or_default(),
+        ),
+        ExecuteMsg::RegisterGenericJob {
+            oracle_script_id,
+            schema,
+            calldata,
+            ask_count,
+            min_count,
+            update_interval,
+        } => try_register_generic_job(
+            deps,
+            info,
+            oracle_script_id,
+            schema,
+            calldata,
+            ask_count,
+            min_count,
+            update_interval,
         ),
-        ExecuteMsg::UpdateJobData { job_id } => try_update_job_data(deps, env, job_id),
+        ExecuteMsg::UpdateJobData { job_id } => try_update_job_data(deps, env, info, job_id),
+        ExecuteMsg::UpdateJobsData { job_ids, merge } => {
+            try_update_jobs_data(deps, env, info, job_ids, merge)
+        }
+        ExecuteMsg::UpdateAllDueJobs { limit, merge } => {
+            try_update_all_due_jobs(deps, env, info, limit, merge)
+        }
+        ExecuteMsg::SetAggregation {
+            symbol,
+            method,
+            min_sources,
+            max_age,
+        } => try_set_aggregation(deps, info, symbol, method, min_sources, max_age),
+        ExecuteMsg::RemoveAggregation { symbol } => try_remove_aggregation(deps, info, symbol),
+        ExecuteMsg::SetPriceSources { symbol, sources } => {
+            try_set_price_sources(deps, info, symbol, sources)
+        }
+        ExecuteMsg::OverridePrice {
+            symbol,
+            rate,
+            expires_at,
+        } => try_override_price(deps, env, info, symbol, rate, expires_at),
+        ExecuteMsg::RemovePriceOverride { symbol } => try_remove_price_override(deps, info, symbol),
+        ExecuteMsg::Subscribe { symbols } => try_subscribe(deps, info, symbols),
+        ExecuteMsg::Unsubscribe { symbols } => try_unsubscribe(deps, info, symbols),
+        ExecuteMsg::SetRequester { address, allowed } => {
+            try_set_requester(deps, info, address, allowed)
+        }
+        ExecuteMsg::RequestPrices(request) => try_request_prices(deps, env, info, request),
+        ExecuteMsg::SetUpdatePrice {
+            job_id,
+            update_price,
+        } => try_set_update_price(deps, info, job_id, update_price),
+        ExecuteMsg::WithdrawFees {
+            denom,
+            amount,
+            recipient,
+        } => try_withdraw_fees(deps, info, denom, amount, recipient),
+        ExecuteMsg::SetKeeperReward { keeper_reward } => {
+            try_set_keeper_reward(deps, info, keeper_reward)
+        }
+        ExecuteMsg::FundRewards {} => try_fund_rewards(deps, info),
+        ExecuteMsg::ClaimRewards {} => try_claim_rewards(deps, info),
+        ExecuteMsg::SetTickBudget { tick_budget } => try_set_tick_budget(deps, info, tick_budget),
+        ExecuteMsg::SetUpdateInterval { job_id, interval } => {
+            try_set_update_interval(deps, info, job_id, interval)
+        }
+        ExecuteMsg::SetResultRetention { result_retention } => {
+            try_set_result_retention(deps, info, result_retention)
+        }
+        ExecuteMsg::ReprocessResult { job_id, request_id } => {
+            try_reprocess_result(deps, env, info, job_id, request_id)
+        }
+        ExecuteMsg::SetFollowUps { job_id, follow_ups } => {
+            try_set_follow_ups(deps, info, job_id, follow_ups)
+        }
+        ExecuteMsg::SetOracleScript {
+            oracle_script_id,
+            oracle_script,
+        } => try_set_oracle_script(deps, info, oracle_script_id, oracle_script),
+        ExecuteMsg::RemoveOracleScript { oracle_script_id } => {
+            try_remove_oracle_script(deps, info, oracle_script_id)
+        }
+        ExecuteMsg::SetVrfRequest { vrf_request } => try_set_vrf_request(deps, info, vrf_request),
+        ExecuteMsg::RequestRandomness { seed } => try_request_randomness(deps, env, info, seed),
+        ExecuteMsg::SetDefaultRequest { default_request } => {
+            try_set_default_request(deps, info, default_request)
+        }
+        ExecuteMsg::SetAllowedSymbols { symbols, allowed } => {
+            try_set_allowed_symbols(deps, info, symbols, allowed)
+        }
+        ExecuteMsg::UpdateSymbols {
+            symbols,
+            oracle_script_id,
+        } => try_update_symbols(deps, env, info, symbols, oracle_script_id),
+        ExecuteMsg::Receive(cw20_msg) => try_receive_cw20(deps, env, info, cw20_msg),
+        ExecuteMsg::SetCw20UpdatePrice {
+            token,
+            update_price,
+        } => try_set_cw20_update_price(deps, info, token, update_price),
+        ExecuteMsg::WithdrawCw20Fees {
+            token,
+            amount,
+            recipient,
+        } => try_withdraw_cw20_fees(deps, info, token, amount, recipient),
     }
 }
 
@@ -150,42 +470,76 @@ pub fn try_set_channel(
 ///
 /// - **multiplier** is an object of type [`u64`] the multiplier to use to multiply the oracle price by.
 ///
-/// - **ask_count** is an object of type [`u64`] which is the number of BandChain validators that are requested to respond to this oracle request.
+/// - **ask_count** is an [`Option`] of type [`u64`] which is the number of BandChain validators that are requested to respond to this oracle request.
+///   Defaults to the registered oracle script's ask count.
 ///
-/// - **min_count** is an object of type [`u64`] which is the minimum number of validators necessary for the request to proceed to the execution phase.
+/// - **min_count** is an [`Option`] of type [`u64`] which is the minimum number of validators necessary for the request to proceed to the execution phase.
+///   Defaults to the registered oracle script's min count.
+///
+/// - **update_interval** is an [`Option`] of type [`u64`] which is the target interval (in seconds) between successful updates.
+///
+/// - **calldata_variant** is an object of type [`CalldataVariant`] which is the calldata layout expected by the oracle script.
+#[allow(clippy::too_many_arguments)]
 pub fn try_register_job(
     deps: DepsMut,
     info: MessageInfo,
     oracle_script_id: u64,
     symbols: Vec<String>,
     multiplier: u64,
-    ask_count: u64,
-    min_count: u64,
+    ask_count: Option<u64>,
+    min_count: Option<u64>,
+    update_interval: Option<u64>,
+    calldata_variant: CalldataVariant,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     if config.owner != info.sender {
         return Err(ContractError::Unauthorized {});
     }
+    if multiplier == 0 {
+        return Err(ContractError::ZeroMultiplier {});
+    }
+    if let CalldataVariant::PerSymbolMultipliers { multipliers } = &calldata_variant {
+        if multipliers.len() != symbols.len() || multipliers.contains(&0) {
+            return Err(ContractError::InvalidMultipliers {});
+        }
+    }
 
     let new_job_count = JOB_COUNT.load(deps.storage)? + 1;
     let job_id = format!("{}-{}", JOB_ID_PREFIX, new_job_count);
     JOB_COUNT.save(deps.storage, &new_job_count)?;
 
-    let calldata = PriceDataInput {
-        symbol: symbols.clone(),
-        multiplier,
+    let calldata = calldata_variant.encode_obi(symbols.clone(), multiplier)?;
+
+    // Jobs of registered oracle scripts must match their schema and kind
+    let oracle_script = ORACLE_SCRIPTS.may_load(deps.storage, oracle_script_id)?;
+    if let Some(oracle_script) = &oracle_script {
+        if oracle_script.kind != OracleScriptKind::PriceFeed {
+            return Err(ContractError::WrongOracleScriptKind { oracle_script_id });
+        }
+        validate_calldata(oracle_script, &calldata)?;
     }
-    .encode_obi()?;
+    let (ask_count, min_count) = request_params(
+        oracle_script_id,
+        oracle_script.as_ref(),
+        ask_count,
+        min_count,
+    )?;
 
     let job = Job {
         oracle_script_id,
         symbols,
-        multiplier,
+        multiplier: Some(multiplier),
         calldata,
         ask_count,
         min_count,
+        update_price: None,
+        update_interval: update_interval.filter(|interval| *interval > 0),
+        follow_ups: vec![],
+        schema: None,
+        calldata_variant,
     };
     JOBS.save(deps.storage, job_id.as_str(), &job)?;
+    reindex_due_job(deps.storage, &job_id, &job, None)?;
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "register_new_job"),
@@ -194,120 +548,4757 @@ pub fn try_register_job(
 }
 
 /// ## Description
-/// Sends out a new IBC oracle request for the specified job
+/// Register a new oracle request job for an oracle script returning arbitrary data.
+/// The results of the job are stored decoded with the output schema instead of as prices.
 ///
 /// ## Params
 /// - **deps** is an object of type [`DepsMut`].
 ///
 /// - **info** is an object of type [`MessageInfo`].
 ///
-/// - **job_id** is an object of type [`String`] which is the ID of the oracle request job to update.
-pub fn try_update_job_data(
+/// - **oracle_script_id** is an object of type [`u64`] which is the ID of the oracle script on BandChain to query the data from.
+///
+/// - **schema** is an [`Option`] of type [`String`] which is the OBI schema of the oracle script.
+///   Defaults to the registered oracle script's schema.
+///
+/// - **calldata** is an object of type [`String`] which is the calldata of the requests, as a JSON document matching the input schema.
+///
+/// - **ask_count** is an [`Option`] of type [`u64`] which is the number of BandChain validators that are requested to respond to this oracle request.
+///   Defaults to the registered oracle script's ask count.
+///
+/// - **min_count** is an [`Option`] of type [`u64`] which is the minimum number of validators necessary for the request to proceed to the execution phase.
+///   Defaults to the registered oracle script's min count.
+///
+/// - **update_interval** is an [`Option`] of type [`u64`] which is the target interval (in seconds) between successful updates.
+#[allow(clippy::too_many_arguments)]
+pub fn try_register_generic_job(
     deps: DepsMut,
-    env: Env,
-    job_id: String,
+    info: MessageInfo,
+    oracle_script_id: u64,
+    schema: Option<String>,
+    calldata: String,
+    ask_count: Option<u64>,
+    min_count: Option<u64>,
+    update_interval: Option<u64>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    if config.channel == String::new() {
-        return Err(ContractError::ChannelNotSet {});
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
     }
 
-    let job = match JOBS.may_load(deps.storage, &job_id) {
-        Ok(Some(data)) => data,
-        Ok(None) => return Err(ContractError::JobNotFound {}),
-        Err(e) => return Err(ContractError::Std(e)),
+    let oracle_script = ORACLE_SCRIPTS.may_load(deps.storage, oracle_script_id)?;
+    if let Some(oracle_script) = &oracle_script {
+        if oracle_script.kind != OracleScriptKind::Generic {
+            return Err(ContractError::WrongOracleScriptKind { oracle_script_id });
+        }
+    }
+    let schema = match (schema, &oracle_script) {
+        (Some(schema), _) => schema,
+        (None, Some(oracle_script)) => oracle_script.schema.clone(),
+        (None, None) => return Err(ContractError::MissingSchema { oracle_script_id }),
     };
+    let calldata = ObiSchema::parse(&schema)?
+        .encode_input(&JsonValue::from_slice(calldata.as_bytes())?)
+        .map_err(|e| ContractError::InvalidCalldata {
+            reason: e.to_string(),
+        })?;
+    let (ask_count, min_count) = request_params(
+        oracle_script_id,
+        oracle_script.as_ref(),
+        ask_count,
+        min_count,
+    )?;
 
-    Ok(Response::new()
-        .add_attributes(vec![
-            attr("action", "update_data"),
-            attr("channel", config.channel.clone()),
-            attr("job_id", job_id.clone()),
-        ])
-        .add_message(IbcMsg::SendPacket {
-            channel_id: config.channel,
-            data: to_binary(&OracleRequestPacket {
-                client_id: job_id,
-                oracle_script_id: job.oracle_script_id,
-                calldata: job.calldata,
-                ask_count: job.ask_count,
-                min_count: job.min_count,
-                fee_limit: coins(1000000u128, "uband"),
-                prepare_gas: 100000u64,
-                execute_gas: 4000000u64,
-            })?,
-            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(300u64)),
-        }))
+    let new_job_count = JOB_COUNT.load(deps.storage)? + 1;
+    let job_id = format!("{}-{}", JOB_ID_PREFIX, new_job_count);
+    JOB_COUNT.save(deps.storage, &new_job_count)?;
+
+    let job = Job {
+        oracle_script_id,
+        symbols: vec![],
+        multiplier: None,
+        calldata,
+        ask_count,
+        min_count,
+        update_price: None,
+        update_interval: update_interval.filter(|interval| *interval > 0),
+        follow_ups: vec![],
+        schema: Some(schema),
+        calldata_variant: CalldataVariant::default(),
+    };
+    JOBS.save(deps.storage, job_id.as_str(), &job)?;
+    reindex_due_job(deps.storage, &job_id, &job, None)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_generic_job"),
+        attr("job_id", job_id),
+    ]))
 }
 
 /// ## Description
-/// Exposes all the queries available in the contract.
+/// Returns the ask and min count of a new job, falling back to the defaults of its oracle script.
+/// The min count must be between 1 and the ask count.
 ///
 /// ## Params
-/// - **deps** is an object of type [`Deps`].
+/// - **oracle_script_id** is an object of type [`u64`] which is the ID of the oracle script of the job.
 ///
-/// - **_env** is an object of type [`Env`].
+/// - **oracle_script** is an [`Option`] of type [`OracleScript`] which is the registered oracle script, if any.
 ///
-/// - **msg** is an object of type [`QueryMsg`].
+/// - **ask_count** is an [`Option`] of type [`u64`] which is the requested ask count.
 ///
-/// ## Commands
-/// - **QueryMsg::Config {}** Returns general contract parameters using a custom [`ConfigResponse`] structure.
+/// - **min_count** is an [`Option`] of type [`u64`] which is the requested min count.
+fn request_params(
+    oracle_script_id: u64,
+    oracle_script: Option<&OracleScript>,
+    ask_count: Option<u64>,
+    min_count: Option<u64>,
+) -> Result<(u64, u64), ContractError> {
+    let (ask_count, min_count) = match (ask_count, min_count, oracle_script) {
+        (Some(ask_count), Some(min_count), _) => (ask_count, min_count),
+        (ask_count, min_count, Some(oracle_script)) => (
+            ask_count.unwrap_or(oracle_script.ask_count),
+            min_count.unwrap_or(oracle_script.min_count),
+        ),
+        _ => return Err(ContractError::MissingRequestParams { oracle_script_id }),
+    };
+    if min_count == 0 || min_count > ask_count {
+        return Err(ContractError::InvalidRequestParams {});
+    }
+
+    Ok((ask_count, min_count))
+}
+
+/// ## Description
+/// Register an oracle script along with its schema and default request parameters.
 ///
-/// - **QueryMsg::Job { job_id }** Returns information about the specified job using a custom [`Job`] structure.
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
 ///
-/// - **QueryMsg::Price { symbol }** Returns the latest price for the specified asset symbol using a custom [`PriceData`] structure.
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Job { job_id } => to_binary(&query_job(deps, job_id)?),
-        QueryMsg::Price { symbol } => to_binary(&query_price(deps, symbol)?),
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **oracle_script_id** is an object of type [`u64`] which is the ID of the oracle script on BandChain.
+///
+/// - **oracle_script** is an object of type [`OracleScript`] which is the oracle script details.
+pub fn try_set_oracle_script(
+    deps: DepsMut,
+    info: MessageInfo,
+    oracle_script_id: u64,
+    oracle_script: OracleScript,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let schema = ObiSchema::parse(&oracle_script.schema)?;
+    // Price feed results are decoded as a single list of rates or per-symbol responses
+    if oracle_script.kind == OracleScriptKind::PriceFeed && !is_price_output(&schema.output) {
+        return Err(ContractError::InvalidObiSchema {
+            reason: "price feed scripts must output a single list of rates or responses"
+                .to_string(),
+        });
     }
+    if oracle_script.min_count == 0 || oracle_script.min_count > oracle_script.ask_count {
+        return Err(ContractError::InvalidRequestParams {});
+    }
+    ORACLE_SCRIPTS.save(deps.storage, oracle_script_id, &oracle_script)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_oracle_script"),
+        attr("oracle_script_id", oracle_script_id.to_string()),
+        attr("schema", oracle_script.schema),
+    ]))
 }
 
 /// ## Description
-/// Returns general contract parameters using a custom [`ConfigResponse`] structure.
+/// Remove an oracle script from the registry.
 ///
 /// ## Params
-/// - **deps** is an object of type [`Deps`].
-fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **oracle_script_id** is an object of type [`u64`] which is the ID of the oracle script on BandChain.
+pub fn try_remove_oracle_script(
+    deps: DepsMut,
+    info: MessageInfo,
+    oracle_script_id: u64,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    Ok(ConfigResponse {
-        owner: config.owner.to_string(),
-        channel: config.channel,
-    })
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ORACLE_SCRIPTS.remove(deps.storage, oracle_script_id);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_oracle_script"),
+        attr("oracle_script_id", oracle_script_id.to_string()),
+    ]))
 }
 
 /// ## Description
-/// Returns information about the specified job using a custom [`Job`] structure.
+/// Checks that OBI-encoded calldata matches the input schema of an oracle script.
 ///
 /// ## Params
-/// - **deps** is an object of type [`Deps`].
-/// - **job_id** is the ID of the registered job to query the information for.
-fn query_job(deps: Deps, job_id: String) -> StdResult<Job> {
-    JOBS.load(deps.storage, &job_id)
+/// - **oracle_script** is an object of type [`OracleScript`] which is the oracle script the calldata is for.
+///
+/// - **calldata** is the OBI-encoded calldata.
+pub fn validate_calldata(
+    oracle_script: &OracleScript,
+    calldata: &[u8],
+) -> Result<(), ContractError> {
+    let schema = ObiSchema::parse(&oracle_script.schema)?;
+    let mut remaining = calldata;
+    schema
+        .input
+        .decode(&mut remaining)
+        .map_err(|e| ContractError::InvalidCalldata {
+            reason: e.to_string(),
+        })?;
+    if !remaining.is_empty() {
+        return Err(ContractError::InvalidCalldata {
+            reason: format!("{} trailing bytes", remaining.len()),
+        });
+    }
+
+    Ok(())
 }
 
 /// ## Description
-/// Returns the latest price for the specified asset symbol using a custom [`PriceData`] structure.
+/// Sends out a new IBC oracle request for the specified job.
+/// If the job has an update price, the caller must attach it, and it is escrowed until the request resolves.
+/// Otherwise no funds may be attached.
 ///
 /// ## Params
-/// - **deps** is an object of type [`Deps`].
-/// - **symbol** is symbol of the asset to query the latest price data for,
-fn query_price(deps: Deps, symbol: String) -> StdResult<PriceData> {
-    PRICES.load(deps.storage, &symbol)
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **job_id** is an object of type [`String`] which is the ID of the oracle request job to update.
+pub fn try_update_job_data(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    job_id: String,
+) -> Result<Response, ContractError> {
+    let job = match JOBS.may_load(deps.storage, &job_id) {
+        Ok(Some(data)) => data,
+        Ok(None) => return Err(ContractError::JobNotFound {}),
+        Err(e) => return Err(ContractError::Std(e)),
+    };
+
+    match &job.update_price {
+        Some(update_price) => {
+            if info.funds.len() != 1 || info.funds[0] != *update_price {
+                return Err(ContractError::InvalidFunds {
+                    expected: update_price.to_string(),
+                });
+            }
+        }
+        None => nonpayable(&info)?,
+    }
+
+    request_job_update(
+        deps,
+        env,
+        info.sender,
+        job_id,
+        job.update_price.map(Payment::Native),
+    )
 }
 
 /// ## Description
-/// Exposes the migrate functionality in the contract.
+/// Request and update oracle data for each of the specified request job IDs.
+/// Jobs that do not exist, are not due yet, already have a request in flight or are not covered
+/// by the attached funds are skipped and reported instead of failing the whole batch.
 ///
 /// ## Params
-/// - **_deps** is an object of type [`DepsMut`].
+/// - **deps** is an object of type [`DepsMut`].
 ///
-/// - **_env** is an object of type [`Env`].
+/// - **env** is an object of type [`Env`].
 ///
-/// - **_msg** is an object of type [`MigrateMsg`].
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::new())
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **job_ids** is a vector of type [`String`] which are the IDs of the oracle request jobs to update.
+///
+/// - **merge** is whether jobs sharing the same oracle script, multiplier, ask count and min count
+///   are merged into a single request.
+pub fn try_update_jobs_data(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    job_ids: Vec<String>,
+    merge: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.channel == String::new() {
+        return Err(ContractError::ChannelNotSet {});
+    }
+
+    let mut funds = info.funds;
+    let mut messages = vec![];
+    let mut updated = vec![];
+    let mut skipped = vec![];
+    let mut groups: BTreeMap<RequestParams, Vec<(String, String, Job)>> = BTreeMap::new();
+    for job_id in job_ids {
+        let job = match JOBS.may_load(deps.storage, &job_id)? {
+            Some(job) => job,
+            None => {
+                skipped.push(format!("{}:not_found", job_id));
+                continue;
+            }
+        };
+        // A job with an update interval is not requested again before it is due
+        if let Some(due_at) = job_due_at(deps.storage, &job_id, &job)? {
+            if env.block.time.seconds() < due_at {
+                skipped.push(format!("{}:cooldown", job_id));
+                continue;
+            }
+        }
+
+        // Each job's update price is taken out of the attached funds
+        let fund_index = match &job.update_price {
+            Some(update_price) => match funds.iter().position(|coin| {
+                coin.denom == update_price.denom && coin.amount >= update_price.amount
+            }) {
+                Some(index) => Some(index),
+                None => {
+                    skipped.push(format!("{}:insufficient_funds", job_id));
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        let payment = job.update_price.clone().map(Payment::Native);
+        // Only jobs sharing the plain symbols and multiplier calldata can be merged
+        let reserved = match (merge, &job.calldata_variant, job.multiplier) {
+            (true, CalldataVariant::SymbolsMultiplier, Some(multiplier)) => {
+                reserve_job_request(deps.storage, &env, info.sender.clone(), &job_id, payment, 0)
+                    .map(|(job, client_id, refunds)| {
+                        groups
+                            .entry((
+                                job.oracle_script_id,
+                                multiplier,
+                                job.ask_count,
+                                job.min_count,
+                            ))
+                            .or_default()
+                            .push((job_id.clone(), client_id, job));
+                        refunds
+                    })
+            }
+            _ => prepare_job_request(
+                deps.storage,
+                &env,
+                &config.channel,
+                info.sender.clone(),
+                &job_id,
+                payment,
+                0,
+            ),
+        };
+        match reserved {
+            Ok(job_messages) => messages.extend(job_messages),
+            Err(ContractError::RequestInFlight { .. }) => {
+                skipped.push(format!("{}:in_flight", job_id));
+                continue;
+            }
+            Err(e) => return Err(e),
+        }
+        if let (Some(index), Some(update_price)) = (fund_index, &job.update_price) {
+            funds[index].amount -= update_price.amount;
+        }
+        updated.push(job_id);
+    }
+
+    // Compatible jobs share a single request over the union of their symbols
+    for ((oracle_script_id, multiplier, ask_count, min_count), jobs) in groups {
+        if jobs.len() == 1 {
+            let (_, client_id, job) = jobs.into_iter().next().unwrap();
+            messages.push(
+                oracle_request_msg(
+                    deps.storage,
+                    &env,
+                    config.channel.clone(),
+                    client_id,
+                    oracle_script_id,
+                    job.calldata,
+                    ask_count,
+                    min_count,
+                )?
+                .into(),
+            );
+            continue;
+        }
+
+        let mut symbols: Vec<String> = vec![];
+        for (_, _, job) in jobs.iter() {
+            for symbol in job.symbols.iter() {
+                if !symbols.contains(symbol) {
+                    symbols.push(symbol.clone());
+                }
+            }
+        }
+        let calldata = PriceDataInput {
+            symbol: symbols.clone(),
+            multiplier,
+        }
+        .encode_obi()?;
+
+        let new_request_count = REQUEST_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        let client_id = format!("{}-{}", BATCH_ID_PREFIX, new_request_count);
+        REQUEST_COUNT.save(deps.storage, &new_request_count)?;
+
+        // The merged jobs wait on the batch's client ID, and earn a single keeper reward between them
+        let mut rewarded = false;
+        for (job_id, _, _) in jobs.iter() {
+            let mut pending = PENDING_REQUESTS.load(deps.storage, job_id)?;
+            pending.client_id = client_id.clone();
+            pending.rewarded &= !rewarded;
+            rewarded |= pending.rewarded;
+            PENDING_REQUESTS.save(deps.storage, job_id, &pending)?;
+        }
+        BATCH_REQUESTS.save(
+            deps.storage,
+            &client_id,
+            &BatchRequest {
+                job_ids: jobs.into_iter().map(|(job_id, _, _)| job_id).collect(),
+                symbols,
+            },
+        )?;
+
+        messages.push(
+            oracle_request_msg(
+                deps.storage,
+                &env,
+                config.channel.clone(),
+                client_id,
+                oracle_script_id,
+                calldata,
+                ask_count,
+                min_count,
+            )?
+            .into(),
+        );
+    }
+
+    // Whatever was not spent on the sent requests goes back to the caller
+    funds.retain(|coin| !coin.amount.is_zero());
+    if !funds.is_empty() {
+        messages.push(
+            BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: funds,
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "update_jobs_data"),
+            attr("channel", config.channel),
+            attr("job_ids", updated.join(",")),
+            attr("skipped", skipped.join(",")),
+        ])
+        .add_messages(messages))
+}
+
+/// ## Description
+/// Request and update oracle data for the jobs that are due for an update, most overdue first.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **limit** is an [`Option`] of type [`u32`] which is the maximum number of jobs to update.
+///
+/// - **merge** is whether compatible jobs are merged into a single request.
+pub fn try_update_all_due_jobs(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+    merge: bool,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let job_ids = load_due_jobs(deps.storage, env.block.time.seconds(), limit)?
+        .into_iter()
+        .map(|due_job| due_job.job_id)
+        .collect();
+
+    try_update_jobs_data(deps, env, info, job_ids, merge)
+}
+
+/// ## Description
+/// Sends out a new IBC oracle request for the specified job on behalf of the requester,
+/// escrowing the attached payment until the request resolves.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **requester** is an object of type [`Addr`] which is the address that triggered the request.
+///
+/// - **job_id** is an object of type [`String`] which is the ID of the oracle request job to update.
+///
+/// - **payment** is an [`Option`] of type [`Payment`] which is the funds paid for the request, if any.
+pub fn request_job_update(
+    deps: DepsMut,
+    env: Env,
+    requester: Addr,
+    job_id: String,
+    payment: Option<Payment>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.channel == String::new() {
+        return Err(ContractError::ChannelNotSet {});
+    }
+
+    let messages = prepare_job_request(
+        deps.storage,
+        &env,
+        &config.channel,
+        requester,
+        &job_id,
+        payment,
+        0,
+    )?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "update_data"),
+            attr("channel", config.channel),
+            attr("job_id", job_id),
+        ])
+        .add_messages(messages))
+}
+
+/// ## Description
+/// Records a new in-flight request for the specified job and builds the messages sending it out,
+/// preceded by the refund of an expired request's escrow if there is one.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **channel** is the channel to send the request on.
+///
+/// - **requester** is an object of type [`Addr`] which is the address that triggered the request.
+///
+/// - **job_id** is the ID of the oracle request job to update.
+///
+/// - **payment** is an [`Option`] of type [`Payment`] which is the funds paid for the request, if any.
+///
+/// - **depth** is the number of follow-ups that led to the request.
+pub fn prepare_job_request(
+    storage: &mut dyn Storage,
+    env: &Env,
+    channel: &str,
+    requester: Addr,
+    job_id: &str,
+    payment: Option<Payment>,
+    depth: u32,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let (job, client_id, mut messages) =
+        reserve_job_request(storage, env, requester, job_id, payment, depth)?;
+    messages.push(
+        oracle_request_msg(
+            storage,
+            env,
+            channel.to_string(),
+            client_id,
+            job.oracle_script_id,
+            job.calldata,
+            job.ask_count,
+            job.min_count,
+        )?
+        .into(),
+    );
+    Ok(messages)
+}
+
+/// ## Description
+/// Records a new in-flight request for the specified job without sending it, returning the job and
+/// the client ID of the request along with the refund of an expired request's escrow if there is one.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **requester** is an object of type [`Addr`] which is the address that triggered the request.
+///
+/// - **job_id** is the ID of the oracle request job to update.
+///
+/// - **payment** is an [`Option`] of type [`Payment`] which is the funds paid for the request, if any.
+///
+/// - **depth** is the number of follow-ups that led to the request.
+pub fn reserve_job_request(
+    storage: &mut dyn Storage,
+    env: &Env,
+    requester: Addr,
+    job_id: &str,
+    payment: Option<Payment>,
+    depth: u32,
+) -> Result<(Job, String, Vec<CosmosMsg>), ContractError> {
+    let job = match JOBS.may_load(storage, job_id) {
+        Ok(Some(data)) => data,
+        Ok(None) => return Err(ContractError::JobNotFound {}),
+        Err(e) => return Err(ContractError::Std(e)),
+    };
+
+    // A request that never came back is replaced after its expiry, refunding its escrow
+    let now = env.block.time.seconds();
+    let mut messages = vec![];
+    if let Some(pending) = PENDING_REQUESTS.may_load(storage, job_id)? {
+        if is_in_flight(&pending, now) {
+            return Err(ContractError::RequestInFlight {
+                job_id: job_id.to_string(),
+            });
+        }
+        record_request_outcome(
+            storage,
+            job_id,
+            &pending,
+            RequestOutcome::Replaced,
+            None,
+            None,
+            now,
+        )?;
+        if let Some(payment) = pending.payment {
+            messages.push(refund_msg(payment, &pending.requester)?);
+        }
+    }
+    record_request_sent(storage, job_id)?;
+
+    // Only updating a due job earns a keeper reward, so that spamming updates earns nothing.
+    // Requests sent by the contract itself on a chain tick earn no reward either.
+    let due = job_due_at(storage, job_id, &job)?.map_or(false, |due_at| due_at <= now);
+    let rewarded = due && requester != env.contract.address;
+
+    // Each request gets its own client ID, so that packets of a replaced request are told apart
+    let new_request_count = REQUEST_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    let client_id = format!("{}:{}", job_id, new_request_count);
+    REQUEST_COUNT.save(storage, &new_request_count)?;
+    PENDING_REQUESTS.save(
+        storage,
+        job_id,
+        &PendingRequest {
+            requester,
+            payment,
+            sent_at: now,
+            client_id: client_id.clone(),
+            rewarded,
+            depth,
+        },
+    )?;
+    JOB_STATUS.update(storage, job_id, |status| -> StdResult<_> {
+        let mut status: JobStatus = status.unwrap_or_default();
+        status.last_request = Some(now);
+        Ok(status)
+    })?;
+
+    Ok((job, client_id, messages))
+}
+
+/// ## Description
+/// Returns the ID of the job a request was sent for, given the client ID it was sent with.
+///
+/// ## Params
+/// - **client_id** is an object of type [`str`] which is the unique identifier of the oracle request.
+pub fn client_job_id(client_id: &str) -> &str {
+    match client_id.split_once(':') {
+        Some((job_id, _)) => job_id,
+        None => client_id,
+    }
+}
+
+/// ## Description
+/// Requests the follow-up jobs of a job whose update succeeded, on behalf of the contract itself.
+/// Follow-ups beyond [`MAX_FOLLOW_UP_DEPTH`] or with a request already in flight are skipped.
+/// Returns the messages sending the requests along with the IDs of the requested jobs.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **job** is an object of type [`Job`] which is the job whose update succeeded.
+///
+/// - **depth** is the number of follow-ups that led to the job's request.
+pub fn follow_up_requests(
+    storage: &mut dyn Storage,
+    env: &Env,
+    job: &Job,
+    depth: u32,
+) -> StdResult<(Vec<CosmosMsg>, Vec<String>)> {
+    if job.follow_ups.is_empty() || depth >= MAX_FOLLOW_UP_DEPTH {
+        return Ok((vec![], vec![]));
+    }
+
+    let config = CONFIG.load(storage)?;
+    let mut messages = vec![];
+    let mut job_ids = vec![];
+    for job_id in job.follow_ups.iter() {
+        match prepare_job_request(
+            storage,
+            env,
+            &config.channel,
+            env.contract.address.clone(),
+            job_id,
+            None,
+            depth + 1,
+        ) {
+            Ok(job_messages) => messages.extend(job_messages),
+            Err(ContractError::Std(e)) => return Err(e),
+            Err(_) => continue,
+        }
+        job_ids.push(job_id.clone());
+    }
+
+    Ok((messages, job_ids))
+}
+
+/// ## Description
+/// Returns whether a pending request may still resolve. Requests that never came back
+/// are considered expired after [`PENDING_REQUEST_EXPIRY`] seconds.
+///
+/// ## Params
+/// - **pending** is an object of type [`PendingRequest`] which is the request to check.
+///
+/// - **now** is the current block time in seconds.
+pub fn is_in_flight(pending: &PendingRequest, now: u64) -> bool {
+    now < pending.sent_at + PENDING_REQUEST_EXPIRY
+}
+
+/// ## Description
+/// Builds the IBC message sending an [`OracleRequestPacket`] to BandChain. The gas and fee
+/// parameters of registered oracle scripts are used, falling back to the contract defaults.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **channel** is an object of type [`String`] which is the channel to send the request on.
+///
+/// - **client_id** is an object of type [`String`] which is the unique identifier of the oracle request.
+///
+/// - **oracle_script_id** is an object of type [`u64`] which is the ID of the oracle script on BandChain to query the data from.
+///
+/// - **calldata** is an object of type [`Vec<u8>`] which is the OBI-encoded calldata of the request.
+///
+/// - **ask_count** is an object of type [`u64`] which is the number of BandChain validators that are requested to respond to this oracle request.
+///
+/// - **min_count** is an object of type [`u64`] which is the minimum number of validators necessary for the request to proceed to the execution phase.
+#[allow(clippy::too_many_arguments)]
+pub fn oracle_request_msg(
+    storage: &dyn Storage,
+    env: &Env,
+    channel: String,
+    client_id: String,
+    oracle_script_id: u64,
+    calldata: Vec<u8>,
+    ask_count: u64,
+    min_count: u64,
+) -> StdResult<IbcMsg> {
+    // Registered oracle scripts carry their own gas and fee parameters
+    let (fee_limit, prepare_gas, execute_gas) =
+        match ORACLE_SCRIPTS.may_load(storage, oracle_script_id)? {
+            Some(oracle_script) => (
+                oracle_script.fee_limit,
+                oracle_script.prepare_gas,
+                oracle_script.execute_gas,
+            ),
+            None => (coins(FEE_LIMIT, FEE_DENOM), PREPARE_GAS, EXECUTE_GAS),
+        };
+
+    Ok(IbcMsg::SendPacket {
+        channel_id: channel,
+        data: to_binary(&OracleRequestPacket {
+            client_id,
+            oracle_script_id,
+            calldata,
+            ask_count,
+            min_count,
+            fee_limit,
+            prepare_gas,
+            execute_gas,
+        })?,
+        timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(REQUEST_TIMEOUT)),
+    })
+}
+
+/// ## Description
+/// Set the policy used to combine the prices reported by multiple jobs for a symbol.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **symbol** is an object of type [`String`] which is the symbol of the asset to set the policy for.
+///
+/// - **method** is an object of type [`AggregationMethod`] which is the method used to combine the source prices.
+///
+/// - **min_sources** is an object of type [`u64`] which is the minimum number of fresh sources required to publish a price.
+///
+/// - **max_age** is an object of type [`u64`] which is the maximum age (in seconds) of a fresh source price.
+pub fn try_set_aggregation(
+    deps: DepsMut,
+    info: MessageInfo,
+    symbol: String,
+    method: AggregationMethod,
+    min_sources: u64,
+    max_age: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if min_sources == 0 {
+        return Err(ContractError::InvalidAggregation {});
+    }
+    if let AggregationMethod::PrimaryWithFallback { primary_job_id } = &method {
+        if !JOBS.has(deps.storage, primary_job_id) {
+            return Err(ContractError::JobNotFound {});
+        }
+    }
+
+    AGGREGATIONS.save(
+        deps.storage,
+        &symbol,
+        &Aggregation {
+            method,
+            min_sources,
+            max_age,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_aggregation"),
+        attr("symbol", symbol),
+    ]))
+}
+
+/// ## Description
+/// Remove the aggregation policy of a symbol, so that the latest reported price is published again.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **symbol** is an object of type [`String`] which is the symbol of the asset to remove the policy for.
+pub fn try_remove_aggregation(
+    deps: DepsMut,
+    info: MessageInfo,
+    symbol: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    AGGREGATIONS.remove(deps.storage, &symbol);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_aggregation"),
+        attr("symbol", symbol),
+    ]))
+}
+
+/// ## Description
+/// Set the ordered list of jobs the price of a symbol is served from.
+/// An empty list removes the price sources, serving the published price again.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **symbol** is an object of type [`String`] which is the symbol of the asset to set the price sources for.
+///
+/// - **sources** is an object of type [`Vec<PriceSource>`] which is the list of jobs to serve the price from, in order of preference.
+pub fn try_set_price_sources(
+    deps: DepsMut,
+    info: MessageInfo,
+    symbol: String,
+    sources: Vec<PriceSource>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if sources.is_empty() {
+        PRICE_SOURCES.remove(deps.storage, &symbol);
+    } else {
+        for source in sources.iter() {
+            let job = match JOBS.may_load(deps.storage, &source.job_id)? {
+                Some(job) => job,
+                None => return Err(ContractError::JobNotFound {}),
+            };
+            if !job.symbols.contains(&symbol) {
+                return Err(ContractError::SymbolNotCovered {
+                    job_id: source.job_id.clone(),
+                    symbol,
+                });
+            }
+        }
+        PRICE_SOURCES.save(deps.storage, &symbol, &sources)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_price_sources"),
+        attr("symbol", symbol),
+        attr(
+            "sources",
+            sources
+                .iter()
+                .map(|source| source.job_id.as_str())
+                .collect::<Vec<&str>>()
+                .join(","),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Pin the price of a symbol until the given expiry, which must be at most a week away.
+/// Oracle updates are still recorded underneath the override.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **symbol** is an object of type [`String`] which is the symbol of the asset to pin the price for.
+///
+/// - **rate** is an object of type [`Decimal`] which is the price to serve while the override is active.
+///
+/// - **expires_at** is an object of type [`u64`] which is the UNIX epoch time (in seconds) at which the override expires.
+pub fn try_override_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    symbol: String,
+    rate: Decimal,
+    expires_at: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if expires_at <= env.block.time.seconds() {
+        return Err(ContractError::InvalidOverrideExpiry {});
+    }
+    if expires_at - env.block.time.seconds() > MAX_OVERRIDE_DURATION {
+        return Err(ContractError::OverrideTooLong {
+            max_duration: MAX_OVERRIDE_DURATION,
+        });
+    }
+
+    PRICE_OVERRIDES.save(deps.storage, &symbol, &PriceOverride { rate, expires_at })?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "override_price"),
+            attr("symbol", symbol.clone()),
+        ])
+        .add_event(Event::new("price_override").add_attributes(vec![
+            attr("action", "set"),
+            attr("symbol", symbol),
+            attr("rate", rate.to_string()),
+            attr("expires_at", expires_at.to_string()),
+        ])))
+}
+
+/// ## Description
+/// Remove the price override of a symbol, serving the oracle price again.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **symbol** is an object of type [`String`] which is the symbol of the asset to remove the override for.
+pub fn try_remove_price_override(
+    deps: DepsMut,
+    info: MessageInfo,
+    symbol: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PRICE_OVERRIDES.remove(deps.storage, &symbol);
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "remove_price_override"),
+            attr("symbol", symbol.clone()),
+        ])
+        .add_event(
+            Event::new("price_override")
+                .add_attributes(vec![attr("action", "remove"), attr("symbol", symbol)]),
+        ))
+}
+
+/// ## Description
+/// Subscribe the calling contract to price updates of the specified symbols.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **symbols** is an object of type [`Vec<String>`] which is the list of symbols to receive price updates for.
+pub fn try_subscribe(
+    deps: DepsMut,
+    info: MessageInfo,
+    symbols: Vec<String>,
+) -> Result<Response, ContractError> {
+    let contract_info: StdResult<ContractInfoResponse> =
+        deps.querier
+            .query(&QueryRequest::Wasm(WasmQuery::ContractInfo {
+                contract_addr: info.sender.to_string(),
+            }));
+    if contract_info.is_err() {
+        return Err(ContractError::SubscriberNotContract {});
+    }
+
+    for symbol in symbols.iter() {
+        if SUBSCRIPTIONS.has(deps.storage, (symbol, &info.sender)) {
+            continue;
+        }
+        let subscriber_count = SUBSCRIPTIONS
+            .prefix(symbol)
+            .keys_raw(deps.storage, None, None, Order::Ascending)
+            .count();
+        if subscriber_count >= MAX_SUBSCRIBERS_PER_SYMBOL {
+            return Err(ContractError::TooManySubscribers {
+                symbol: symbol.clone(),
+            });
+        }
+        SUBSCRIPTIONS.save(deps.storage, (symbol, &info.sender), &Empty {})?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "subscribe"),
+        attr("subscriber", info.sender),
+        attr("symbols", symbols.join(",")),
+    ]))
+}
+
+/// ## Description
+/// Unsubscribe the calling contract from price updates of the specified symbols.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **symbols** is an object of type [`Vec<String>`] which is the list of symbols to stop receiving price updates for.
+pub fn try_unsubscribe(
+    deps: DepsMut,
+    info: MessageInfo,
+    symbols: Vec<String>,
+) -> Result<Response, ContractError> {
+    for symbol in symbols.iter() {
+        SUBSCRIPTIONS.remove(deps.storage, (symbol, &info.sender));
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "unsubscribe"),
+        attr("subscriber", info.sender),
+        attr("symbols", symbols.join(",")),
+    ]))
+}
+
+/// ## Description
+/// Allow or disallow a contract to make one-off price and randomness requests.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **address** is an object of type [`String`] which is the address of the contract.
+///
+/// - **allowed** is an object of type [`bool`] which is whether the contract is allowed to make one-off price and randomness requests.
+pub fn try_set_requester(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    allowed: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let requester = deps.api.addr_validate(&address)?;
+    if allowed {
+        ALLOWED_REQUESTERS.save(deps.storage, &requester, &Empty {})?;
+    } else {
+        ALLOWED_REQUESTERS.remove(deps.storage, &requester);
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_requester"),
+        attr("requester", requester),
+        attr("allowed", allowed.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Sends out a one-off IBC oracle request for the specified symbols on behalf of an allowed contract.
+/// The result is delivered back to the caller using a [`RequesterExecuteMsg`] callback.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **request** is an object of type [`PriceRequest`] which describes the prices to request.
+pub fn try_request_prices(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    request: PriceRequest,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    send_price_request(deps, env, info.sender, request, None)
+}
+
+/// ## Description
+/// Sends out a one-off IBC oracle request on behalf of an allowed contract,
+/// escrowing the attached payment until the request resolves.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **requester** is an object of type [`Addr`] which is the address of the requesting contract.
+///
+/// - **request** is an object of type [`PriceRequest`] which describes the prices to request.
+///
+/// - **payment** is an [`Option`] of type [`Payment`] which is the funds paid for the request, if any.
+pub fn send_price_request(
+    deps: DepsMut,
+    env: Env,
+    requester: Addr,
+    request: PriceRequest,
+    payment: Option<Payment>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.channel == String::new() {
+        return Err(ContractError::ChannelNotSet {});
+    }
+    if !ALLOWED_REQUESTERS.has(deps.storage, &requester) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if request.symbols.is_empty() {
+        return Err(ContractError::EmptySymbols {});
+    }
+    if request.multiplier == 0 {
+        return Err(ContractError::ZeroMultiplier {});
+    }
+
+    let new_request_count = REQUEST_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    let client_id = format!("{}-{}", REQUEST_ID_PREFIX, new_request_count);
+    REQUEST_COUNT.save(deps.storage, &new_request_count)?;
+
+    let calldata = PriceDataInput {
+        symbol: request.symbols.clone(),
+        multiplier: request.multiplier,
+    }
+    .encode_obi()?;
+
+    ON_DEMAND_REQUESTS.save(
+        deps.storage,
+        &client_id,
+        &OnDemandRequest {
+            requester: requester.clone(),
+            symbols: request.symbols,
+            multiplier: request.multiplier,
+            payment,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "request_prices"),
+            attr("channel", config.channel.clone()),
+            attr("requester", requester),
+            attr("client_id", client_id.clone()),
+        ])
+        .add_message(oracle_request_msg(
+            deps.storage,
+            &env,
+            config.channel,
+            client_id,
+            request.oracle_script_id,
+            calldata,
+            request.ask_count,
+            request.min_count,
+        )?))
+}
+
+/// ## Description
+/// Set the funds callers must attach to request an update of a job.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **job_id** is an object of type [`String`] which is the ID of the oracle request job to set the update price for.
+///
+/// - **update_price** is an [`Option`] of type [`Coin`] which is the funds to attach to each update request.
+pub fn try_set_update_price(
+    deps: DepsMut,
+    info: MessageInfo,
+    job_id: String,
+    update_price: Option<Coin>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut job = match JOBS.may_load(deps.storage, &job_id)? {
+        Some(job) => job,
+        None => return Err(ContractError::JobNotFound {}),
+    };
+    job.update_price = update_price.filter(|price| !price.amount.is_zero());
+    JOBS.save(deps.storage, &job_id, &job)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_update_price"),
+        attr("job_id", job_id),
+        attr(
+            "update_price",
+            job.update_price
+                .map(|price| price.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Withdraw the update fees collected by the contract.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **denom** is an object of type [`String`] which is the denom of the fees to withdraw.
+///
+/// - **amount** is an [`Option`] of type [`Uint128`] which is the amount to withdraw, defaulting to the whole collected balance.
+///
+/// - **recipient** is an [`Option`] of type [`String`] which is the address to send the fees to, defaulting to the owner.
+pub fn try_withdraw_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    amount: Option<Uint128>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collected = TREASURY.may_load(deps.storage, &denom)?.unwrap_or_default();
+    let amount = amount.unwrap_or(collected);
+    if amount.is_zero() || amount > collected {
+        return Err(ContractError::InsufficientFees {});
+    }
+    TREASURY.save(deps.storage, &denom, &(collected - amount))?;
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => config.owner,
+    };
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "withdraw_fees"),
+            attr("recipient", recipient.clone()),
+            attr("amount", amount.to_string() + &denom),
+        ])
+        .add_message(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), denom),
+        }))
+}
+
+/// ## Description
+/// Set the reward paid from the reward pool to the address that triggered a successful job update.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **keeper_reward** is an [`Option`] of type [`Coin`] which is the reward paid for each successful job update.
+pub fn try_set_keeper_reward(
+    deps: DepsMut,
+    info: MessageInfo,
+    keeper_reward: Option<Coin>,
+) -> Result<Response, ContractError> {
+    let keeper_reward = keeper_reward.filter(|reward| !reward.amount.is_zero());
+    CONFIG.update(
+        deps.storage,
+        |mut config| -> Result<Config, ContractError> {
+            if config.owner != info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            config.keeper_reward = keeper_reward.clone();
+
+            Ok(config)
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_keeper_reward"),
+        attr(
+            "keeper_reward",
+            keeper_reward
+                .map(|reward| reward.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Fund the keeper reward pool with the attached native coins.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+pub fn try_fund_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    if info.funds.is_empty() {
+        return Err(ContractError::NoFunds {});
+    }
+
+    for coin in info.funds.iter() {
+        REWARD_POOL.update(deps.storage, &coin.denom, |pool| -> StdResult<_> {
+            Ok(pool.unwrap_or_default().checked_add(coin.amount)?)
+        })?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "fund_rewards"),
+        attr("funder", info.sender),
+        attr(
+            "amount",
+            info.funds
+                .iter()
+                .map(|coin| coin.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Claim the keeper rewards earned by the caller.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+pub fn try_claim_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let earned = KEEPER_REWARDS
+        .prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, KeeperRewards)>>>()?;
+
+    let mut claimed = vec![];
+    for (denom, mut rewards) in earned {
+        if rewards.claimable.is_zero() {
+            continue;
+        }
+        claimed.push(Coin {
+            denom: denom.clone(),
+            amount: rewards.claimable,
+        });
+        rewards.claimable = Uint128::zero();
+        KEEPER_REWARDS.save(deps.storage, (&info.sender, &denom), &rewards)?;
+    }
+    if claimed.is_empty() {
+        return Err(ContractError::NoRewards {});
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "claim_rewards"),
+            attr("keeper", info.sender.clone()),
+        ])
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: claimed,
+        }))
+}
+
+/// ## Description
+/// Set the maximum number of due jobs requested by each chain tick.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **tick_budget** is an object of type [`u32`] which is the maximum number of jobs requested per tick.
+pub fn try_set_tick_budget(
+    deps: DepsMut,
+    info: MessageInfo,
+    tick_budget: u32,
+) -> Result<Response, ContractError> {
+    CONFIG.update(
+        deps.storage,
+        |mut config| -> Result<Config, ContractError> {
+            if config.owner != info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            config.tick_budget = Some(tick_budget);
+
+            Ok(config)
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_tick_budget")
+        .add_attribute("tick_budget", tick_budget.to_string()))
+}
+
+/// ## Description
+/// Set the target interval between successful updates of the specified job.
+/// Only jobs with an update interval are reported as due and requested by chain ticks.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **job_id** is an object of type [`String`] which is the ID of the oracle request job to set the update interval for.
+///
+/// - **interval** is an [`Option`] of type [`u64`] which is the target interval (in seconds) between successful updates.
+pub fn try_set_update_interval(
+    deps: DepsMut,
+    info: MessageInfo,
+    job_id: String,
+    interval: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut job = match JOBS.may_load(deps.storage, &job_id)? {
+        Some(job) => job,
+        None => return Err(ContractError::JobNotFound {}),
+    };
+    let previous_due_at = job_due_at(deps.storage, &job_id, &job)?;
+    job.update_interval = interval.filter(|interval| *interval > 0);
+    JOBS.save(deps.storage, &job_id, &job)?;
+    reindex_due_job(deps.storage, &job_id, &job, previous_due_at)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_update_interval"),
+        attr("job_id", job_id),
+        attr(
+            "update_interval",
+            job.update_interval
+                .map(|interval| interval.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Set the number of raw results kept for each job.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **result_retention** is an object of type [`u32`] which is the number of raw results kept per job.
+pub fn try_set_result_retention(
+    deps: DepsMut,
+    info: MessageInfo,
+    result_retention: u32,
+) -> Result<Response, ContractError> {
+    CONFIG.update(
+        deps.storage,
+        |mut config| -> Result<Config, ContractError> {
+            if config.owner != info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            config.result_retention = Some(result_retention);
+
+            Ok(config)
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_result_retention")
+        .add_attribute("result_retention", result_retention.to_string()))
+}
+
+/// ## Description
+/// Decode a stored raw result of a job again with the job's current parameters and republish the
+/// resulting prices. Only the latest stored result of the job can be reprocessed, so that newer
+/// prices are never replaced by older ones.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **job_id** is an object of type [`String`] which is the ID of the oracle request job the result was received for.
+///
+/// - **request_id** is an object of type [`u64`] which is the BandChain request ID that produced the result.
+pub fn try_reprocess_result(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    job_id: String,
+    request_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let job = match JOBS.may_load(deps.storage, &job_id)? {
+        Some(job) => job,
+        None => return Err(ContractError::JobNotFound {}),
+    };
+    let raw_result = match RAW_RESULTS.may_load(deps.storage, (&job_id, request_id))? {
+        Some(raw_result) => raw_result,
+        None => return Err(ContractError::ResultNotFound { job_id, request_id }),
+    };
+    let newer = RAW_RESULTS
+        .prefix(&job_id)
+        .range(
+            deps.storage,
+            Some(Bound::exclusive(request_id)),
+            None,
+            Order::Ascending,
+        )
+        .next()
+        .is_some();
+    if newer {
+        return Err(ContractError::StaleResult { job_id });
+    }
+
+    if let Some(schema) = &job.schema {
+        let value = ObiSchema::parse(schema)?.decode_output(&raw_result.result)?;
+        JOB_RESULTS.save(
+            deps.storage,
+            &job_id,
+            &JobResult {
+                result: value.to_string(),
+                bandchain_request_id: request_id,
+                bandchain_resolve_time: raw_result.resolve_time,
+            },
+        )?;
+
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "reprocess_result"),
+            attr("job_id", job_id),
+            attr("request_id", request_id.to_string()),
+        ]));
+    }
+
+    let rates = job
+        .calldata_variant
+        .decode_rates(&raw_result.symbols, &raw_result.result)?;
+    if raw_result.symbols.len() != rates.len() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Result and Calldata length mismatched",
+        )));
+    }
+    // A job symbol missing from the result is treated as unavailable, as in a merged request
+    let result_rates: BTreeMap<&String, Option<u64>> =
+        raw_result.symbols.iter().zip(rates).collect();
+    let job_rates: Vec<Option<u64>> = job
+        .symbols
+        .iter()
+        .map(|symbol| result_rates.get(symbol).copied().flatten())
+        .collect();
+
+    let now = env.block.time.seconds();
+    let stored = store_job_prices(
+        deps.storage,
+        now,
+        &job_id,
+        &job,
+        &job_rates,
+        request_id,
+        raw_result.resolve_time,
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(subscriber_callbacks(deps.storage, now, stored.published)?)
+        .add_attributes(vec![
+            attr("action", "reprocess_result"),
+            attr("job_id", job_id),
+            attr("request_id", request_id.to_string()),
+            attr("updated_symbols", stored.updated_symbols.join(",")),
+            attr("failed_symbols", stored.failed_symbols.join(",")),
+            attr("insufficient_sources", stored.pending_symbols.join(",")),
+        ]))
+}
+
+/// ## Description
+/// Set the jobs requested once an update of the specified job succeeds.
+/// Fails if the follow-ups would eventually lead back to the job.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **job_id** is an object of type [`String`] which is the ID of the oracle request job to set the follow-ups for.
+///
+/// - **follow_ups** is a vector of type [`String`] which are the IDs of the jobs to request after each successful update.
+pub fn try_set_follow_ups(
+    deps: DepsMut,
+    info: MessageInfo,
+    job_id: String,
+    follow_ups: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut job = match JOBS.may_load(deps.storage, &job_id)? {
+        Some(job) => job,
+        None => return Err(ContractError::JobNotFound {}),
+    };
+
+    // Walk every job reachable from the new follow-ups, which must never include the job itself
+    let mut visited: Vec<String> = vec![];
+    let mut queue = follow_ups.clone();
+    while let Some(next_id) = queue.pop() {
+        if next_id == job_id {
+            return Err(ContractError::FollowUpCycle { job_id });
+        }
+        if visited.contains(&next_id) {
+            continue;
+        }
+        match JOBS.may_load(deps.storage, &next_id)? {
+            Some(next) => queue.extend(next.follow_ups),
+            None => return Err(ContractError::JobNotFound {}),
+        }
+        visited.push(next_id);
+    }
+
+    job.follow_ups = follow_ups;
+    JOBS.save(deps.storage, &job_id, &job)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_follow_ups"),
+        attr("job_id", job_id),
+        attr("follow_ups", job.follow_ups.join(",")),
+    ]))
+}
+
+/// ## Description
+/// Set the request parameters used to request randomness from the VRF oracle script.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **vrf_request** is an [`Option`] of type [`VrfRequest`] which is the VRF request
+///   parameters, or `None` to disable randomness requests.
+pub fn try_set_vrf_request(
+    deps: DepsMut,
+    info: MessageInfo,
+    vrf_request: Option<VrfRequest>,
+) -> Result<Response, ContractError> {
+    CONFIG.update(
+        deps.storage,
+        |mut config| -> Result<Config, ContractError> {
+            if config.owner != info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            config.vrf_request = vrf_request;
+
+            Ok(config)
+        },
+    )?;
+
+    Ok(Response::new().add_attribute("action", "set_vrf_request"))
+}
+
+/// ## Description
+/// Sends out a request to the VRF oracle script on behalf of an allowed contract.
+/// The randomness is delivered back to the caller using a [`RequesterExecuteMsg`] callback
+/// and can also be polled with the returned request ID.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **seed** is an object of type [`Binary`] which is the seed the randomness is derived from.
+pub fn try_request_randomness(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    seed: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.channel == String::new() {
+        return Err(ContractError::ChannelNotSet {});
+    }
+    if !ALLOWED_REQUESTERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let vrf_request = match config.vrf_request {
+        Some(vrf_request) => vrf_request,
+        None => return Err(ContractError::VrfRequestNotSet {}),
+    };
+    if seed.is_empty() {
+        return Err(ContractError::EmptySeed {});
+    }
+
+    let new_request_count = REQUEST_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    let client_id = format!("{}-{}", VRF_ID_PREFIX, new_request_count);
+    REQUEST_COUNT.save(deps.storage, &new_request_count)?;
+
+    let time = env.block.time.seconds();
+    let calldata = VrfInput {
+        seed: seed.to_vec(),
+        time,
+        worker_contract: info.sender.to_string(),
+    }
+    .encode_obi()?;
+
+    RANDOMNESS.save(
+        deps.storage,
+        &client_id,
+        &Randomness {
+            requester: info.sender.clone(),
+            seed,
+            time,
+            status: RandomnessStatus::Pending,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "request_randomness"),
+            attr("channel", config.channel.clone()),
+            attr("requester", info.sender),
+            attr("client_id", client_id.clone()),
+        ])
+        .add_message(oracle_request_msg(
+            deps.storage,
+            &env,
+            config.channel,
+            client_id,
+            vrf_request.oracle_script_id,
+            calldata,
+            vrf_request.ask_count,
+            vrf_request.min_count,
+        )?))
+}
+
+/// ## Description
+/// Set the request parameters used to update symbols that have no registered job.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **default_request** is an [`Option`] of type [`DefaultRequest`] which is the default request
+///   parameters, or `None` to disable symbol updates.
+pub fn try_set_default_request(
+    deps: DepsMut,
+    info: MessageInfo,
+    default_request: Option<DefaultRequest>,
+) -> Result<Response, ContractError> {
+    CONFIG.update(
+        deps.storage,
+        |mut config| -> Result<Config, ContractError> {
+            if config.owner != info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            if let Some(default_request) = &default_request {
+                if default_request.multiplier == 0 {
+                    return Err(ContractError::ZeroMultiplier {});
+                }
+            }
+            config.default_request = default_request;
+
+            Ok(config)
+        },
+    )?;
+
+    Ok(Response::new().add_attribute("action", "set_default_request"))
+}
+
+/// ## Description
+/// Allow or disallow symbols to be updated without a registered job.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **symbols** is a vector of type [`String`] which are the asset symbols.
+///
+/// - **allowed** is an object of type [`bool`] which is whether the symbols may be updated without a registered job.
+pub fn try_set_allowed_symbols(
+    deps: DepsMut,
+    info: MessageInfo,
+    symbols: Vec<String>,
+    allowed: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for symbol in symbols.iter() {
+        if allowed {
+            ALLOWED_SYMBOLS.save(deps.storage, symbol, &Empty {})?;
+        } else {
+            ALLOWED_SYMBOLS.remove(deps.storage, symbol);
+        }
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_allowed_symbols"),
+        attr("symbols", symbols.join(",")),
+        attr("allowed", allowed.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Sends out an IBC oracle request for the specified allowed symbols using the default request
+/// parameters. The resulting prices are stored just like the ones of a registered job.
+/// Apart from the owner, each symbol can only be requested once per [`SYMBOL_REQUEST_COOLDOWN`].
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **symbols** is a vector of type [`String`] which are the asset symbols to update.
+///
+/// - **oracle_script_id** is an [`Option`] of type [`u64`] which is the oracle script ID to use
+///   instead of the default one. Only the owner may set it.
+pub fn try_update_symbols(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    symbols: Vec<String>,
+    oracle_script_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    if config.channel == String::new() {
+        return Err(ContractError::ChannelNotSet {});
+    }
+    // Any other oracle script could write arbitrary rates into the stored prices
+    if oracle_script_id.is_some() && config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let default_request = match config.default_request {
+        Some(default_request) => default_request,
+        None => return Err(ContractError::DefaultRequestNotSet {}),
+    };
+
+    if symbols.is_empty() {
+        return Err(ContractError::EmptySymbols {});
+    }
+    // Every request spends the contract's fee on BandChain, so anyone but the owner
+    // may only request each symbol once per cooldown
+    let now = env.block.time.seconds();
+    for symbol in symbols.iter() {
+        if !ALLOWED_SYMBOLS.has(deps.storage, symbol) {
+            return Err(ContractError::SymbolNotAllowed {
+                symbol: symbol.clone(),
+            });
+        }
+        if config.owner != info.sender {
+            if let Some(last_request) = SYMBOL_LAST_REQUEST.may_load(deps.storage, symbol)? {
+                let retry_at = last_request.saturating_add(SYMBOL_REQUEST_COOLDOWN);
+                if now < retry_at {
+                    return Err(ContractError::SymbolRequestedRecently {
+                        symbol: symbol.clone(),
+                        retry_at,
+                    });
+                }
+            }
+        }
+    }
+    for symbol in symbols.iter() {
+        SYMBOL_LAST_REQUEST.save(deps.storage, symbol, &now)?;
+    }
+
+    let new_request_count = REQUEST_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    let client_id = format!("{}-{}", SYMBOL_REQUEST_ID_PREFIX, new_request_count);
+    REQUEST_COUNT.save(deps.storage, &new_request_count)?;
+
+    let calldata = PriceDataInput {
+        symbol: symbols.clone(),
+        multiplier: default_request.multiplier,
+    }
+    .encode_obi()?;
+
+    SYMBOL_REQUESTS.save(
+        deps.storage,
+        &client_id,
+        &SymbolRequest {
+            symbols: symbols.clone(),
+            multiplier: default_request.multiplier,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "update_symbols"),
+            attr("channel", config.channel.clone()),
+            attr("client_id", client_id.clone()),
+            attr("symbols", symbols.join(",")),
+        ])
+        .add_message(oracle_request_msg(
+            deps.storage,
+            &env,
+            config.channel,
+            client_id,
+            oracle_script_id.unwrap_or(default_request.oracle_script_id),
+            calldata,
+            default_request.ask_count,
+            default_request.min_count,
+        )?))
+}
+
+/// Receives cw20 tokens paying for the oracle request embedded in the [`Cw20HookMsg`].
+/// Only tokens allowlisted by the owner are accepted, at their configured price.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **cw20_msg** is an object of type [`Cw20ReceiveMsg`] which is the cw20 transfer notification.
+pub fn try_receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let update_price = match CW20_UPDATE_PRICES.may_load(deps.storage, &info.sender)? {
+        Some(update_price) => update_price,
+        None => return Err(ContractError::UnsupportedToken {}),
+    };
+    if cw20_msg.amount != update_price {
+        return Err(ContractError::InvalidFunds {
+            expected: format!("{}{}", update_price, info.sender),
+        });
+    }
+
+    let requester = deps.api.addr_validate(&cw20_msg.sender)?;
+    let payment = Payment::Cw20(Cw20CoinVerified {
+        address: info.sender,
+        amount: cw20_msg.amount,
+    });
+
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::UpdateJobData { job_id } => {
+            request_job_update(deps, env, requester, job_id, Some(payment))
+        }
+        Cw20HookMsg::RequestPrices(request) => {
+            send_price_request(deps, env, requester, request, Some(payment))
+        }
+    }
+}
+
+/// ## Description
+/// Set the amount of a cw20 token accepted as payment for an oracle request.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **token** is an object of type [`String`] which is the address of the cw20 token contract.
+///
+/// - **update_price** is an [`Option`] of type [`Uint128`] which is the amount of tokens to send with each request.
+pub fn try_set_cw20_update_price(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+    update_price: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let token = deps.api.addr_validate(&token)?;
+    match update_price {
+        Some(update_price) if !update_price.is_zero() => {
+            CW20_UPDATE_PRICES.save(deps.storage, &token, &update_price)?
+        }
+        _ => CW20_UPDATE_PRICES.remove(deps.storage, &token),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_cw20_update_price"),
+        attr("token", token),
+        attr("update_price", update_price.unwrap_or_default().to_string()),
+    ]))
+}
+
+/// ## Description
+/// Withdraw the cw20 update fees collected by the contract.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **info** is an object of type [`MessageInfo`].
+///
+/// - **token** is an object of type [`String`] which is the address of the cw20 token contract.
+///
+/// - **amount** is an [`Option`] of type [`Uint128`] which is the amount to withdraw, defaulting to the whole collected balance.
+///
+/// - **recipient** is an [`Option`] of type [`String`] which is the address to send the fees to, defaulting to the owner.
+pub fn try_withdraw_cw20_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+    amount: Option<Uint128>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let token = deps.api.addr_validate(&token)?;
+    let collected = CW20_TREASURY
+        .may_load(deps.storage, &token)?
+        .unwrap_or_default();
+    let amount = amount.unwrap_or(collected);
+    if amount.is_zero() || amount > collected {
+        return Err(ContractError::InsufficientFees {});
+    }
+    CW20_TREASURY.save(deps.storage, &token, &(collected - amount))?;
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => config.owner,
+    };
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "withdraw_cw20_fees"),
+            attr("recipient", recipient.clone()),
+            attr("token", token.clone()),
+            attr("amount", amount.to_string()),
+        ])
+        .add_message(refund_msg(
+            Payment::Cw20(Cw20CoinVerified {
+                address: token,
+                amount,
+            }),
+            &recipient,
+        )?))
+}
+
+/// ## Description
+/// Exposes the privileged functions the chain can call on the contract.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **msg** is an object of type [`SudoMsg`].
+///
+/// ## Commands
+/// - **SudoMsg::Tick {}** Send oracle requests for the due jobs, up to the configured tick budget.
+///
+/// - **SudoMsg::SetChannel {
+///   channel
+///   }** Set the IBC channel to be used for the oracle requests.
+///
+/// - **SudoMsg::SetOwner {
+///   owner
+///   }** Set the contract owner.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::Tick {} => sudo_tick(deps, env),
+        SudoMsg::SetChannel { channel } => {
+            CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+                config.channel = channel.clone();
+                Ok(config)
+            })?;
+            Ok(Response::new()
+                .add_attribute("action", "sudo_set_channel")
+                .add_attribute("channel", channel))
+        }
+        SudoMsg::SetOwner { owner } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+                config.owner = owner.clone();
+                Ok(config)
+            })?;
+            Ok(Response::new()
+                .add_attribute("action", "sudo_set_owner")
+                .add_attribute("owner", owner))
+        }
+    }
+}
+
+/// ## Description
+/// Sends oracle requests for the due jobs, up to the configured tick budget.
+/// Requests are made on behalf of the contract itself, so they earn no keeper reward.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+pub fn sudo_tick(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.channel == String::new() {
+        return Err(ContractError::ChannelNotSet {});
+    }
+
+    let tick_budget = config.tick_budget.unwrap_or(DEFAULT_TICK_BUDGET) as usize;
+    let due_jobs = load_due_jobs(deps.storage, env.block.time.seconds(), tick_budget)?;
+
+    let mut messages = vec![];
+    let mut job_ids = vec![];
+    for due_job in due_jobs {
+        messages.extend(prepare_job_request(
+            deps.storage,
+            &env,
+            &config.channel,
+            env.contract.address.clone(),
+            &due_job.job_id,
+            None,
+            0,
+        )?);
+        job_ids.push(due_job.job_id);
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "tick"),
+            attr("channel", config.channel),
+            attr("job_ids", job_ids.join(",")),
+        ])
+        .add_messages(messages))
+}
+
+/// ## Description
+/// Exposes all the queries available in the contract.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **msg** is an object of type [`QueryMsg`].
+///
+/// ## Commands
+/// - **QueryMsg::Config {}** Returns general contract parameters using a custom [`ConfigResponse`] structure.
+///
+/// - **QueryMsg::Job { job_id }** Returns information about the specified job using a custom [`Job`] structure.
+///
+/// - **QueryMsg::Price { symbol }** Returns the latest price for the specified asset symbol using a custom [`PriceResponse`] structure.
+///
+/// - **QueryMsg::Aggregation { symbol }** Returns the aggregation policy of the specified asset symbol using a custom [`Aggregation`] structure.
+///
+/// - **QueryMsg::SourcePrices { symbol }** Returns the latest price reported by each job for the specified asset symbol.
+///
+/// - **QueryMsg::PriceSources { symbol }** Returns the ordered list of jobs the price of the specified asset symbol is served from.
+///
+/// - **QueryMsg::Subscribers { symbol }** Returns the addresses of the contracts subscribed to price updates of the specified asset symbol.
+///
+/// - **QueryMsg::PendingRequest { job_id }** Returns the in-flight request of the specified job using a custom [`PendingRequest`] structure.
+///
+/// - **QueryMsg::Treasury {}** Returns the update fees collected by the contract using a custom [`TreasuryResponse`] structure.
+///
+/// - **QueryMsg::Cw20UpdatePrices {}** Returns the cw20 tokens accepted as payment for oracle requests, with their price per request.
+///
+/// - **QueryMsg::RewardPool {}** Returns the funds available in the keeper reward pool.
+///
+/// - **QueryMsg::KeeperRewards { keeper }** Returns the rewards earned by the specified keeper using a custom [`KeeperRewardsResponse`] structure.
+///
+/// - **QueryMsg::JobStatus { job_id }** Returns the request and success timestamps of the specified job using a custom [`JobStatus`] structure.
+///
+/// - **QueryMsg::DueJobs { limit }** Returns the jobs whose update interval has elapsed and which have no request in flight, most overdue first.
+///
+/// - **QueryMsg::AllowedSymbols {}** Returns the symbols that may be updated without a registered job.
+///
+/// - **QueryMsg::JobHistory { job_id, start_after, limit }** Returns the latest finished requests of the specified job, oldest first.
+///
+/// - **QueryMsg::JobStats { job_id }** Returns the request statistics of the specified job using a custom [`JobStatsResponse`] structure.
+///
+/// - **QueryMsg::SymbolFailures { symbol }** Returns the failure counters of the specified symbol using a custom [`SymbolFailures`] structure.
+///
+/// - **QueryMsg::Randomness { request_id }** Returns the specified randomness request using a custom [`Randomness`] structure.
+///
+/// - **QueryMsg::JobResult { job_id }** Returns the latest result of the specified generic job using a custom [`JobResult`] structure.
+///
+/// - **QueryMsg::RawResults { job_id }** Returns the raw results kept for the specified job, oldest first.
+///
+/// - **QueryMsg::OracleScripts { start_after, limit }** Returns the registered oracle scripts using a custom [`OracleScriptResponse`] structure.
+///
+/// - **QueryMsg::BandchainRequest { request_id }** Returns the response received for the specified BandChain request ID using a custom [`BandchainRequest`] structure.
+///
+/// - **QueryMsg::Provenance { symbol }** Returns the current price of the specified asset symbol along with the BandChain requests that produced it using a custom [`ProvenanceResponse`] structure.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Job { job_id } => to_binary(&query_job(deps, job_id)?),
+        QueryMsg::Price { symbol } => to_binary(&query_price(deps, env, symbol)?),
+        QueryMsg::Aggregation { symbol } => to_binary(&query_aggregation(deps, symbol)?),
+        QueryMsg::SourcePrices { symbol } => to_binary(&query_source_prices(deps, symbol)?),
+        QueryMsg::PriceSources { symbol } => to_binary(&query_price_sources(deps, symbol)?),
+        QueryMsg::Subscribers { symbol } => to_binary(&query_subscribers(deps, symbol)?),
+        QueryMsg::PendingRequest { job_id } => to_binary(&query_pending_request(deps, job_id)?),
+        QueryMsg::Treasury {} => to_binary(&query_treasury(deps)?),
+        QueryMsg::Cw20UpdatePrices {} => to_binary(&query_cw20_update_prices(deps)?),
+        QueryMsg::RewardPool {} => to_binary(&query_reward_pool(deps)?),
+        QueryMsg::KeeperRewards { keeper } => to_binary(&query_keeper_rewards(deps, keeper)?),
+        QueryMsg::JobStatus { job_id } => to_binary(&query_job_status(deps, job_id)?),
+        QueryMsg::DueJobs { limit } => to_binary(&query_due_jobs(deps, env, limit)?),
+        QueryMsg::AllowedSymbols {} => to_binary(&query_allowed_symbols(deps)?),
+        QueryMsg::JobHistory {
+            job_id,
+            start_after,
+            limit,
+        } => to_binary(&query_job_history(deps, job_id, start_after, limit)?),
+        QueryMsg::JobStats { job_id } => to_binary(&query_job_stats(deps, job_id)?),
+        QueryMsg::SymbolFailures { symbol } => to_binary(&query_symbol_failures(deps, symbol)?),
+        QueryMsg::Randomness { request_id } => to_binary(&query_randomness(deps, request_id)?),
+        QueryMsg::JobResult { job_id } => to_binary(&query_job_result(deps, job_id)?),
+        QueryMsg::RawResults { job_id } => to_binary(&query_raw_results(deps, job_id)?),
+        QueryMsg::OracleScripts { start_after, limit } => {
+            to_binary(&query_oracle_scripts(deps, start_after, limit)?)
+        }
+        QueryMsg::BandchainRequest { request_id } => {
+            to_binary(&query_bandchain_request(deps, request_id)?)
+        }
+        QueryMsg::Provenance { symbol } => to_binary(&query_provenance(deps, env, symbol)?),
+    }
+}
+
+/// ## Description
+/// Returns general contract parameters using a custom [`ConfigResponse`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: config.owner.to_string(),
+        channel: config.channel,
+        keeper_reward: config.keeper_reward,
+        tick_budget: config.tick_budget.unwrap_or(DEFAULT_TICK_BUDGET),
+        default_request: config.default_request,
+        result_retention: config.result_retention.unwrap_or(DEFAULT_RESULT_RETENTION),
+        vrf_request: config.vrf_request,
+    })
+}
+
+/// ## Description
+/// Returns information about the specified job using a custom [`Job`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **job_id** is the ID of the registered job to query the information for.
+fn query_job(deps: Deps, job_id: String) -> StdResult<Job> {
+    JOBS.load(deps.storage, &job_id)
+}
+
+/// ## Description
+/// Returns the latest price for the specified asset symbol using a custom [`PriceResponse`] structure.
+/// An active owner override takes precedence over the oracle price.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **env** is an object of type [`Env`].
+/// - **symbol** is symbol of the asset to query the latest price data for,
+fn query_price(deps: Deps, env: Env, symbol: String) -> StdResult<PriceResponse> {
+    let now = env.block.time.seconds();
+    let price = load_oracle_price(deps.storage, now, &symbol);
+
+    match PRICE_OVERRIDES.may_load(deps.storage, &symbol)? {
+        Some(price_override) if price_override.expires_at > now => {
+            let (bandchain_request_id, bandchain_resolve_time) = match price {
+                Ok(price) => (price.bandchain_request_id, price.bandchain_resolve_time),
+                Err(_) => (0, 0),
+            };
+            Ok(PriceResponse {
+                rate: price_override.rate,
+                bandchain_request_id,
+                bandchain_resolve_time,
+                job_id: None,
+                oracle_script_id: None,
+                override_expires_at: Some(price_override.expires_at),
+            })
+        }
+        _ => price,
+    }
+}
+
+/// ## Description
+/// Returns the raw results kept for the specified job, oldest first.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **job_id** is the ID of the registered job to query the raw results for.
+fn query_raw_results(deps: Deps, job_id: String) -> StdResult<Vec<RawResultResponse>> {
+    RAW_RESULTS
+        .prefix(&job_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(request_id, result)| RawResultResponse { request_id, result }))
+        .collect()
+}
+
+/// ## Description
+/// Returns the failure counters of the specified symbol using a custom [`SymbolFailures`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **symbol** is the symbol of the asset to query the failure counters for.
+fn query_symbol_failures(deps: Deps, symbol: String) -> StdResult<SymbolFailures> {
+    Ok(SYMBOL_FAILURES
+        .may_load(deps.storage, &symbol)?
+        .unwrap_or_default())
+}
+
+/// ## Description
+/// Returns the specified randomness request using a custom [`Randomness`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **request_id** is the request ID returned when the randomness was requested.
+fn query_randomness(deps: Deps, request_id: String) -> StdResult<Randomness> {
+    RANDOMNESS.load(deps.storage, &request_id)
+}
+
+/// ## Description
+/// Returns the latest result of the specified generic job using a custom [`JobResult`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **job_id** is the ID of the job to query the result for.
+fn query_job_result(deps: Deps, job_id: String) -> StdResult<JobResult> {
+    JOB_RESULTS.load(deps.storage, &job_id)
+}
+
+/// ## Description
+/// Returns the registered oracle scripts using a custom [`OracleScriptResponse`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **start_after** is the ID of the oracle script to start after.
+/// - **limit** is the maximum number of oracle scripts to return.
+fn query_oracle_scripts(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<OracleScriptResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    ORACLE_SCRIPTS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            item.map(|(oracle_script_id, oracle_script)| OracleScriptResponse {
+                oracle_script_id,
+                oracle_script,
+            })
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns the response received for the specified BandChain request ID using a custom [`BandchainRequest`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **request_id** is the BandChain request ID to query the response for.
+fn query_bandchain_request(deps: Deps, request_id: u64) -> StdResult<BandchainRequest> {
+    BANDCHAIN_REQUESTS.load(deps.storage, request_id)
+}
+
+/// ## Description
+/// Returns the current price of the specified asset symbol along with the BandChain requests
+/// that produced it using a custom [`ProvenanceResponse`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **env** is an object of type [`Env`].
+/// - **symbol** is symbol of the asset to query the provenance for.
+fn query_provenance(deps: Deps, env: Env, symbol: String) -> StdResult<ProvenanceResponse> {
+    let price = query_price(deps, env, symbol.clone())?;
+    let request = BANDCHAIN_REQUESTS.may_load(deps.storage, price.bandchain_request_id)?;
+
+    let sources = SOURCE_PRICES
+        .prefix(&symbol)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (source, price) = item?;
+            let request = BANDCHAIN_REQUESTS.may_load(deps.storage, price.bandchain_request_id)?;
+            Ok(SourceProvenance {
+                source,
+                price,
+                request,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ProvenanceResponse {
+        price,
+        request,
+        sources,
+    })
+}
+
+/// ## Description
+/// Returns the latest oracle price for the specified asset symbol, ignoring owner overrides.
+/// If the symbol has price sources configured, the price of the first job whose data is within its heartbeat is served.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+/// - **now** is the current block time in seconds.
+/// - **symbol** is symbol of the asset to load the latest price data for.
+pub fn load_oracle_price(
+    storage: &dyn Storage,
+    now: u64,
+    symbol: &str,
+) -> StdResult<PriceResponse> {
+    let sources = match PRICE_SOURCES.may_load(storage, symbol)? {
+        Some(sources) => sources,
+        None => {
+            let price = PRICES.load(storage, symbol)?;
+            return Ok(PriceResponse {
+                rate: price.rate,
+                bandchain_request_id: price.bandchain_request_id,
+                bandchain_resolve_time: price.bandchain_resolve_time,
+                job_id: None,
+                oracle_script_id: None,
+                override_expires_at: None,
+            });
+        }
+    };
+
+    for source in sources {
+        let price = match SOURCE_PRICES.may_load(storage, (symbol, &source.job_id))? {
+            Some(price) => price,
+            None => continue,
+        };
+        if now.saturating_sub(price.bandchain_resolve_time) > source.heartbeat {
+            continue;
+        }
+
+        let job = JOBS.load(storage, &source.job_id)?;
+        return Ok(PriceResponse {
+            rate: price.rate,
+            bandchain_request_id: price.bandchain_request_id,
+            bandchain_resolve_time: price.bandchain_resolve_time,
+            job_id: Some(source.job_id),
+            oracle_script_id: Some(job.oracle_script_id),
+            override_expires_at: None,
+        });
+    }
+
+    Err(StdError::generic_err(format!(
+        "No price source within its heartbeat for {}",
+        symbol
+    )))
+}
+
+/// ## Description
+/// Returns the aggregation policy of the specified asset symbol using a custom [`Aggregation`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **symbol** is symbol of the asset to query the aggregation policy for.
+fn query_aggregation(deps: Deps, symbol: String) -> StdResult<Aggregation> {
+    AGGREGATIONS.load(deps.storage, &symbol)
+}
+
+/// ## Description
+/// Returns the latest price reported by each job for the specified asset symbol.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **symbol** is symbol of the asset to query the source prices for.
+fn query_source_prices(deps: Deps, symbol: String) -> StdResult<Vec<SourcePriceResponse>> {
+    SOURCE_PRICES
+        .prefix(&symbol)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(job_id, price)| SourcePriceResponse { job_id, price }))
+        .collect()
+}
+
+/// ## Description
+/// Returns the ordered list of jobs the price of the specified asset symbol is served from.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **symbol** is symbol of the asset to query the price sources for.
+fn query_price_sources(deps: Deps, symbol: String) -> StdResult<Vec<PriceSource>> {
+    Ok(PRICE_SOURCES
+        .may_load(deps.storage, &symbol)?
+        .unwrap_or_default())
+}
+
+/// ## Description
+/// Returns the addresses of the contracts subscribed to price updates of the specified asset symbol.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **symbol** is symbol of the asset to query the subscribers for.
+fn query_subscribers(deps: Deps, symbol: String) -> StdResult<Vec<Addr>> {
+    SUBSCRIPTIONS
+        .prefix(&symbol)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect()
+}
+
+/// ## Description
+/// Returns the latest finished requests of the specified job, oldest first.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **job_id** is the ID of the registered job to query the history for.
+/// - **start_after** is the sequence of the request to start after.
+/// - **limit** is the maximum number of requests to return.
+fn query_job_history(
+    deps: Deps,
+    job_id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<JobHistoryEntry>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    JOB_HISTORY
+        .prefix(&job_id)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect()
+}
+
+/// ## Description
+/// Returns the request statistics of the specified job using a custom [`JobStatsResponse`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **job_id** is the ID of the registered job to query the statistics for.
+fn query_job_stats(deps: Deps, job_id: String) -> StdResult<JobStatsResponse> {
+    let stats = JOB_STATS
+        .may_load(deps.storage, &job_id)?
+        .unwrap_or_default();
+    let average_latency = match stats.finished() {
+        0 => 0,
+        finished => stats.total_latency / finished,
+    };
+
+    Ok(JobStatsResponse {
+        stats,
+        average_latency,
+    })
+}
+
+/// ## Description
+/// Returns the symbols that may be updated without a registered job.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+fn query_allowed_symbols(deps: Deps) -> StdResult<Vec<String>> {
+    ALLOWED_SYMBOLS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect()
+}
+
+/// ## Description
+/// Returns the in-flight request of the specified job using a custom [`PendingRequest`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **job_id** is the ID of the registered job to query the in-flight request for.
+fn query_pending_request(deps: Deps, job_id: String) -> StdResult<PendingRequest> {
+    PENDING_REQUESTS.load(deps.storage, &job_id)
+}
+
+/// ## Description
+/// Returns the update fees collected by the contract using a custom [`TreasuryResponse`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+fn query_treasury(deps: Deps) -> StdResult<TreasuryResponse> {
+    Ok(TreasuryResponse {
+        native: TREASURY
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+            .collect::<StdResult<Vec<Coin>>>()?,
+        cw20: CW20_TREASURY
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(address, amount)| Cw20CoinVerified { address, amount }))
+            .collect::<StdResult<Vec<Cw20CoinVerified>>>()?,
+    })
+}
+
+/// ## Description
+/// Returns the cw20 tokens accepted as payment for oracle requests, with their price per request.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+fn query_cw20_update_prices(deps: Deps) -> StdResult<Vec<Cw20CoinVerified>> {
+    CW20_UPDATE_PRICES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(address, amount)| Cw20CoinVerified { address, amount }))
+        .collect()
+}
+
+/// ## Description
+/// Returns the funds available in the keeper reward pool.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+fn query_reward_pool(deps: Deps) -> StdResult<Vec<Coin>> {
+    REWARD_POOL
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect()
+}
+
+/// ## Description
+/// Returns the rewards earned by the specified keeper using a custom [`KeeperRewardsResponse`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **keeper** is the address of the keeper to query the rewards for.
+fn query_keeper_rewards(deps: Deps, keeper: String) -> StdResult<Vec<KeeperRewardsResponse>> {
+    let keeper = deps.api.addr_validate(&keeper)?;
+    KEEPER_REWARDS
+        .prefix(&keeper)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(denom, rewards)| KeeperRewardsResponse {
+                denom,
+                claimable: rewards.claimable,
+                total_earned: rewards.total_earned,
+            })
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns the request and success timestamps of the specified job using a custom [`JobStatus`] structure.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **job_id** is the ID of the registered job to query the status for.
+fn query_job_status(deps: Deps, job_id: String) -> StdResult<JobStatus> {
+    if !JOBS.has(deps.storage, &job_id) {
+        return Err(StdError::not_found("Job"));
+    }
+    Ok(JOB_STATUS
+        .may_load(deps.storage, &job_id)?
+        .unwrap_or_default())
+}
+
+/// ## Description
+/// Returns the jobs whose update interval has elapsed and which have no request in flight, most overdue first.
+///
+/// ## Params
+/// - **deps** is an object of type [`Deps`].
+/// - **env** is an object of type [`Env`].
+/// - **limit** is the maximum number of jobs to return.
+fn query_due_jobs(deps: Deps, env: Env, limit: Option<u32>) -> StdResult<Vec<DueJobResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    load_due_jobs(deps.storage, env.block.time.seconds(), limit)
+}
+
+/// ## Description
+/// Returns the time (in seconds) at which a job becomes due for an update,
+/// or [`None`] if the job has no update interval.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+/// - **job_id** is the ID of the job.
+/// - **job** is an object of type [`Job`] which is the job itself.
+pub fn job_due_at(storage: &dyn Storage, job_id: &str, job: &Job) -> StdResult<Option<u64>> {
+    let update_interval = match job.update_interval {
+        Some(update_interval) => update_interval,
+        None => return Ok(None),
+    };
+    let last_success = JOB_STATUS
+        .may_load(storage, job_id)?
+        .and_then(|status| status.last_success)
+        .unwrap_or_default();
+    Ok(Some(last_success.saturating_add(update_interval)))
+}
+
+/// ## Description
+/// Moves a job within the due-time index after its update interval or last success changed.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+/// - **job_id** is the ID of the job.
+/// - **job** is an object of type [`Job`] which is the job itself.
+/// - **previous_due_at** is an [`Option`] of type [`u64`] which is the time the job was due at before the change.
+pub fn reindex_due_job(
+    storage: &mut dyn Storage,
+    job_id: &str,
+    job: &Job,
+    previous_due_at: Option<u64>,
+) -> StdResult<()> {
+    if let Some(due_at) = previous_due_at {
+        DUE_JOBS.remove(storage, (due_at, job_id));
+    }
+    if let Some(due_at) = job_due_at(storage, job_id, job)? {
+        DUE_JOBS.save(storage, (due_at, job_id), &Empty {})?;
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Loads the jobs whose update interval has elapsed and which have no request in flight, most overdue first.
+/// Only the due-time index is read, so the cost does not grow with the number of jobs that are not due.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+/// - **now** is the current block time in seconds.
+/// - **limit** is the maximum number of jobs to return.
+pub fn load_due_jobs(
+    storage: &dyn Storage,
+    now: u64,
+    limit: usize,
+) -> StdResult<Vec<DueJobResponse>> {
+    let mut due_jobs = vec![];
+    let due = DUE_JOBS.keys(
+        storage,
+        None,
+        Some(Bound::exclusive((now.saturating_add(1), ""))),
+        Order::Ascending,
+    );
+    for item in due {
+        if due_jobs.len() >= limit {
+            break;
+        }
+        let (due_at, job_id) = item?;
+        if let Some(pending) = PENDING_REQUESTS.may_load(storage, &job_id)? {
+            if is_in_flight(&pending, now) {
+                continue;
+            }
+        }
+        due_jobs.push(DueJobResponse {
+            job_id,
+            overdue_by: now - due_at,
+        });
+    }
+
+    Ok(due_jobs)
+}
+
+/// ## Description
+/// Handles the replies of the submessages sent by the contract.
+/// Failed subscriber and requester callbacks are recorded as attributes without failing the transaction.
+///
+/// ## Params
+/// - **_deps** is an object of type [`DepsMut`].
+///
+/// - **_env** is an object of type [`Env`].
+///
+/// - **msg** is an object of type [`Reply`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match (msg.id, msg.result) {
+        (SUBSCRIBER_CALLBACK_REPLY_ID, SubMsgResult::Err(err)) => Ok(Response::new()
+            .add_attribute("action", "subscriber_callback_failed")
+            .add_attribute("error", err)),
+        (REQUESTER_CALLBACK_REPLY_ID, SubMsgResult::Err(err)) => Ok(Response::new()
+            .add_attribute("action", "requester_callback_failed")
+            .add_attribute("error", err)),
+        _ => Err(ContractError::Unsupported {}),
+    }
+}
+
+/// ## Description
+/// Exposes the migrate functionality in the contract.
+///
+/// ## Params
+/// - **_deps** is an object of type [`DepsMut`].
+///
+/// - **_env** is an object of type [`Env`].
+///
+/// - **_msg** is an object of type [`MigrateMsg`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    Ok(Response::new())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::history::MAX_JOB_HISTORY;
+    use crate::ibc::{ibc_packet_ack, ibc_packet_timeout, make_ack_fail, make_ack_success};
+    use crate::msg::{RequesterExecuteMsg, SubscriberExecuteMsg};
+    use crate::state::JobStats;
+    use crate::testing::{
+        attribute, price_result, receive, register_job, response_packet, run, sent_packets, setup,
+        update_job, update_job_at, CHANNEL,
+    };
+    use cosmwasm_std::testing::{
+        mock_env, mock_ibc_packet_ack, mock_ibc_packet_timeout, mock_info,
+    };
+    use cosmwasm_std::{
+        coin, from_binary, ContractResult, CosmosMsg, IbcAcknowledgement, IbcReceiveResponse,
+        ReplyOn, SubMsg, SystemResult, WasmMsg,
+    };
+    use cw20::Cw20ExecuteMsg;
+
+    fn price(deps: Deps, env: Env, symbol: &str) -> StdResult<PriceResponse> {
+        from_binary(&query(
+            deps,
+            env,
+            QueryMsg::Price {
+                symbol: symbol.to_string(),
+            },
+        )?)
+    }
+
+    #[test]
+    fn price_sources_test() {
+        let mut deps = setup();
+        let primary = register_job(deps.as_mut(), 1, &["BTC"]);
+        let fallback = register_job(deps.as_mut(), 2, &["BTC", "ETH"]);
+        let uncovered = register_job(deps.as_mut(), 3, &["ETH"]);
+
+        // Every source must be a registered job covering the symbol
+        let set_sources = |job_ids: &[&str]| ExecuteMsg::SetPriceSources {
+            symbol: "BTC".to_string(),
+            sources: job_ids
+                .iter()
+                .map(|job_id| PriceSource {
+                    job_id: job_id.to_string(),
+                    heartbeat: 60,
+                })
+                .collect(),
+        };
+        assert!(matches!(
+            run(deps.as_mut(), "owner", &[], set_sources(&[&uncovered])),
+            Err(ContractError::SymbolNotCovered { .. })
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "owner", &[], set_sources(&["tvl-9"])),
+            Err(ContractError::JobNotFound {})
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "keeper", &[], set_sources(&[&primary])),
+            Err(ContractError::Unauthorized {})
+        ));
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            set_sources(&[&primary, &fallback]),
+        )
+        .unwrap();
+
+        // The primary job is served while it is within its heartbeat
+        let now = mock_env().block.time.seconds();
+        let mut packet = response_packet(
+            &update_job(deps.as_mut(), &primary),
+            1,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[100]),
+        );
+        packet.resolve_time = (now - 120).to_string();
+        receive(deps.as_mut(), mock_env(), &packet);
+        let packet = response_packet(
+            &update_job(deps.as_mut(), &fallback),
+            2,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[200, 10]),
+        );
+        receive(deps.as_mut(), mock_env(), &packet);
+        let mut env = mock_env();
+        env.block.time = env.block.time.minus_seconds(90);
+        let served = price(deps.as_ref(), env, "BTC").unwrap();
+        assert_eq!(served.job_id, Some(primary.clone()));
+        assert_eq!(served.oracle_script_id, Some(1));
+
+        // A stale primary job falls back to the next source
+        let served = price(deps.as_ref(), mock_env(), "BTC").unwrap();
+        assert_eq!(served.rate, Decimal::from_ratio(200u64, 1000000u64));
+        assert_eq!(served.bandchain_request_id, 2);
+        assert_eq!(served.job_id, Some(fallback));
+        assert_eq!(served.oracle_script_id, Some(2));
+
+        // Once every source is stale, no price is served
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(61);
+        assert!(price(deps.as_ref(), env, "BTC").is_err());
+
+        // Without sources the latest published price is served again
+        run(deps.as_mut(), "owner", &[], set_sources(&[])).unwrap();
+        let served = price(deps.as_ref(), mock_env(), "BTC").unwrap();
+        assert_eq!(served.bandchain_request_id, 2);
+        assert_eq!(served.job_id, None);
+    }
+
+    #[test]
+    fn price_override_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        let packet = response_packet(
+            &update_job(deps.as_mut(), &job_id),
+            1,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[100]),
+        );
+        receive(deps.as_mut(), mock_env(), &packet);
+
+        // Overrides are owner-only, must expire in the future and last at most a week
+        let now = mock_env().block.time.seconds();
+        let override_price = |expires_at: u64| ExecuteMsg::OverridePrice {
+            symbol: "BTC".to_string(),
+            rate: Decimal::percent(150),
+            expires_at,
+        };
+        assert!(matches!(
+            run(deps.as_mut(), "keeper", &[], override_price(now + 60)),
+            Err(ContractError::Unauthorized {})
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "owner", &[], override_price(now)),
+            Err(ContractError::InvalidOverrideExpiry {})
+        ));
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                override_price(now + MAX_OVERRIDE_DURATION + 1)
+            ),
+            Err(ContractError::OverrideTooLong { .. })
+        ));
+        let res = run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            override_price(now + MAX_OVERRIDE_DURATION),
+        )
+        .unwrap();
+        assert_eq!(res.events[0].ty, "price_override");
+
+        // The override is served until it expires, with oracle updates recorded underneath
+        let packet = response_packet(
+            &update_job(deps.as_mut(), &job_id),
+            2,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[200]),
+        );
+        receive(deps.as_mut(), mock_env(), &packet);
+        let served = price(deps.as_ref(), mock_env(), "BTC").unwrap();
+        assert_eq!(served.rate, Decimal::percent(150));
+        assert_eq!(served.bandchain_request_id, 2);
+        assert_eq!(
+            served.override_expires_at,
+            Some(now + MAX_OVERRIDE_DURATION)
+        );
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(MAX_OVERRIDE_DURATION);
+        let served = price(deps.as_ref(), env, "BTC").unwrap();
+        assert_eq!(served.rate, Decimal::from_ratio(200u64, 1000000u64));
+        assert_eq!(served.override_expires_at, None);
+
+        // Removing the override serves the oracle price right away
+        run(deps.as_mut(), "owner", &[], override_price(now + 60)).unwrap();
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::RemovePriceOverride {
+                symbol: "BTC".to_string(),
+            },
+        )
+        .unwrap();
+        let served = price(deps.as_ref(), mock_env(), "BTC").unwrap();
+        assert_eq!(served.rate, Decimal::from_ratio(200u64, 1000000u64));
+    }
+
+    fn pushed(res: &IbcReceiveResponse) -> Vec<(String, Vec<String>)> {
+        res.messages
+            .iter()
+            .filter_map(|msg| match &msg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) => match from_binary(msg).unwrap() {
+                    SubscriberExecuteMsg::ReceivePriceUpdate { prices } => Some((
+                        contract_addr.clone(),
+                        prices.into_iter().map(|update| update.symbol).collect(),
+                    )),
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn subscription_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC", "ETH"]);
+        let eth_job_id = register_job(deps.as_mut(), 2, &["ETH"]);
+        let subscribe = ExecuteMsg::Subscribe {
+            symbols: vec!["BTC".to_string(), "ETH".to_string()],
+        };
+
+        // Only contracts can subscribe
+        assert!(matches!(
+            run(deps.as_mut(), "keeper", &[], subscribe.clone()),
+            Err(ContractError::SubscriberNotContract {})
+        ));
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(
+                to_binary(&ContractInfoResponse::new(1, "creator")).unwrap(),
+            ))
+        });
+        run(deps.as_mut(), "consumer", &[], subscribe).unwrap();
+
+        // Each subscriber receives one callback with all of its updated symbols
+        let packet = response_packet(
+            &update_job(deps.as_mut(), &job_id),
+            1,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[100, 10]),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert_eq!(
+            pushed(&res),
+            vec![(
+                "consumer".to_string(),
+                vec!["BTC".to_string(), "ETH".to_string()]
+            )]
+        );
+        assert_eq!(res.messages[0].reply_on, ReplyOn::Error);
+
+        // Symbols with an active override are not pushed
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::OverridePrice {
+                symbol: "BTC".to_string(),
+                rate: Decimal::one(),
+                expires_at: mock_env().block.time.seconds() + 60,
+            },
+        )
+        .unwrap();
+        let packet = response_packet(
+            &update_job(deps.as_mut(), &job_id),
+            2,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[100, 10]),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert_eq!(
+            pushed(&res),
+            vec![("consumer".to_string(), vec!["ETH".to_string()])]
+        );
+
+        // Neither are prices from a job other than the symbol's selected price source
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetPriceSources {
+                symbol: "ETH".to_string(),
+                sources: vec![
+                    PriceSource {
+                        job_id: eth_job_id.clone(),
+                        heartbeat: 60,
+                    },
+                    PriceSource {
+                        job_id: job_id.clone(),
+                        heartbeat: 60,
+                    },
+                ],
+            },
+        )
+        .unwrap();
+        let packet = response_packet(
+            &update_job(deps.as_mut(), &eth_job_id),
+            3,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[11]),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert_eq!(
+            pushed(&res),
+            vec![("consumer".to_string(), vec!["ETH".to_string()])]
+        );
+        let packet = response_packet(
+            &update_job(deps.as_mut(), &job_id),
+            4,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[100, 10]),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert_eq!(pushed(&res), vec![]);
+
+        // Unsubscribed contracts receive nothing
+        run(
+            deps.as_mut(),
+            "consumer",
+            &[],
+            ExecuteMsg::Unsubscribe {
+                symbols: vec!["ETH".to_string()],
+            },
+        )
+        .unwrap();
+        let packet = response_packet(
+            &update_job(deps.as_mut(), &eth_job_id),
+            5,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[12]),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert_eq!(pushed(&res), vec![]);
+    }
+
+    fn requester_callbacks(messages: &[SubMsg]) -> Vec<(String, RequesterExecuteMsg)> {
+        messages
+            .iter()
+            .filter_map(|msg| match &msg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) => Some((contract_addr.clone(), from_binary(msg).unwrap())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn request_prices_test() {
+        let mut deps = setup();
+        let request_prices = |symbols: &[&str], multiplier: u64| {
+            ExecuteMsg::RequestPrices(PriceRequest {
+                oracle_script_id: 1,
+                symbols: symbols.iter().map(|symbol| symbol.to_string()).collect(),
+                multiplier,
+                ask_count: 4,
+                min_count: 3,
+            })
+        };
+
+        // Only allowed contracts can request prices, for at least one symbol
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "consumer",
+                &[],
+                request_prices(&["BTC"], 100)
+            ),
+            Err(ContractError::Unauthorized {})
+        ));
+        let set_requester = |allowed: bool| ExecuteMsg::SetRequester {
+            address: "consumer".to_string(),
+            allowed,
+        };
+        assert!(matches!(
+            run(deps.as_mut(), "consumer", &[], set_requester(true)),
+            Err(ContractError::Unauthorized {})
+        ));
+        run(deps.as_mut(), "owner", &[], set_requester(true)).unwrap();
+        assert!(matches!(
+            run(deps.as_mut(), "consumer", &[], request_prices(&[], 100)),
+            Err(ContractError::EmptySymbols {})
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "consumer", &[], request_prices(&["BTC"], 0)),
+            Err(ContractError::ZeroMultiplier {})
+        ));
+
+        // The resolved prices are delivered to the requester without being published
+        let res = run(
+            deps.as_mut(),
+            "consumer",
+            &[],
+            request_prices(&["BTC", "ETH"], 100),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        assert_eq!(request.client_id, "req-1");
+        let packet = response_packet(
+            &request.client_id,
+            1,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[150, 20]),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        let callbacks = requester_callbacks(&res.messages);
+        assert_eq!(callbacks.len(), 1);
+        assert_eq!(callbacks[0].0, "consumer");
+        match &callbacks[0].1 {
+            RequesterExecuteMsg::ReceivePriceResponse { client_id, prices } => {
+                assert_eq!(client_id, "req-1");
+                assert_eq!(prices[0].symbol, "BTC");
+                assert_eq!(prices[0].price.rate, Decimal::percent(150));
+                assert_eq!(prices[1].price.rate, Decimal::percent(20));
+            }
+            msg => panic!("unexpected callback {:?}", msg),
+        }
+        assert!(PRICES.may_load(&deps.storage, "BTC").unwrap().is_none());
+        assert!(!ON_DEMAND_REQUESTS.has(&deps.storage, "req-1"));
+
+        // Failed and timed out requests notify the requester of the failure
+        let res = run(
+            deps.as_mut(),
+            "consumer",
+            &[],
+            request_prices(&["BTC"], 100),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let packet = response_packet(
+            &request.client_id,
+            2,
+            "RESOLVE_STATUS_FAILURE",
+            String::new(),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert!(matches!(
+            &requester_callbacks(&res.messages)[..],
+            [(_, RequesterExecuteMsg::ReceivePriceFailure { client_id, .. })] if client_id == "req-2"
+        ));
+
+        let res = run(
+            deps.as_mut(),
+            "consumer",
+            &[],
+            request_prices(&["BTC"], 100),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let res = ibc_packet_timeout(
+            deps.as_mut(),
+            mock_env(),
+            mock_ibc_packet_timeout(CHANNEL, &request).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(
+            &requester_callbacks(&res.messages)[..],
+            [(_, RequesterExecuteMsg::ReceivePriceFailure { client_id, .. })] if client_id == "req-3"
+        ));
+
+        // Symbols reported with a zero rate are left out of the delivered prices
+        let res = run(
+            deps.as_mut(),
+            "consumer",
+            &[],
+            request_prices(&["BTC", "ETH"], 100),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let packet = response_packet(
+            &request.client_id,
+            4,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[150, 0]),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert!(res.attributes.contains(&attr("failed_symbols", "ETH")));
+        assert!(matches!(
+            &requester_callbacks(&res.messages)[..],
+            [(_, RequesterExecuteMsg::ReceivePriceResponse { prices, .. })]
+                if prices.len() == 1 && prices[0].symbol == "BTC"
+        ));
+
+        // Disallowed contracts can no longer request prices
+        run(deps.as_mut(), "owner", &[], set_requester(false)).unwrap();
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "consumer",
+                &[],
+                request_prices(&["BTC"], 100)
+            ),
+            Err(ContractError::Unauthorized {})
+        ));
+    }
+
+    fn cw20_transfers(messages: &[SubMsg]) -> Vec<(String, String, Uint128)> {
+        messages
+            .iter()
+            .filter_map(|msg| match &msg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) => match from_binary(msg) {
+                    Ok(Cw20ExecuteMsg::Transfer { recipient, amount }) => {
+                        Some((contract_addr.clone(), recipient, amount))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cw20_payment_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        let receive_cw20 = |sender: &str, amount: u128, msg: &Cw20HookMsg| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: sender.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(msg).unwrap(),
+            })
+        };
+        let update = Cw20HookMsg::UpdateJobData {
+            job_id: job_id.clone(),
+        };
+
+        // Only allowlisted tokens are accepted, at their configured price
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "token",
+                &[],
+                receive_cw20("alice", 50, &update)
+            ),
+            Err(ContractError::UnsupportedToken {})
+        ));
+        let set_price = ExecuteMsg::SetCw20UpdatePrice {
+            token: "token".to_string(),
+            update_price: Some(Uint128::new(50)),
+        };
+        assert!(matches!(
+            run(deps.as_mut(), "alice", &[], set_price.clone()),
+            Err(ContractError::Unauthorized {})
+        ));
+        run(deps.as_mut(), "owner", &[], set_price).unwrap();
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "token",
+                &[],
+                receive_cw20("alice", 49, &update)
+            ),
+            Err(ContractError::InvalidFunds { .. })
+        ));
+
+        // A failed request refunds the tokens to the sender
+        let res = run(
+            deps.as_mut(),
+            "token",
+            &[],
+            receive_cw20("alice", 50, &update),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let packet = response_packet(
+            &request.client_id,
+            1,
+            "RESOLVE_STATUS_FAILURE",
+            String::new(),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert_eq!(
+            cw20_transfers(&res.messages),
+            vec![("token".to_string(), "alice".to_string(), Uint128::new(50))]
+        );
+
+        // A successful job update or one-off request collects them
+        let res = run(
+            deps.as_mut(),
+            "token",
+            &[],
+            receive_cw20("alice", 50, &update),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let packet = response_packet(
+            &request.client_id,
+            2,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[100]),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert!(cw20_transfers(&res.messages).is_empty());
+
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetRequester {
+                address: "consumer".to_string(),
+                allowed: true,
+            },
+        )
+        .unwrap();
+        let request_prices = Cw20HookMsg::RequestPrices(PriceRequest {
+            oracle_script_id: 1,
+            symbols: vec!["ETH".to_string()],
+            multiplier: 100,
+            ask_count: 4,
+            min_count: 3,
+        });
+        let res = run(
+            deps.as_mut(),
+            "token",
+            &[],
+            receive_cw20("consumer", 50, &request_prices),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let packet = response_packet(
+            &request.client_id,
+            3,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[100]),
+        );
+        receive(deps.as_mut(), mock_env(), &packet);
+        assert_eq!(
+            CW20_TREASURY
+                .load(&deps.storage, &Addr::unchecked("token"))
+                .unwrap(),
+            Uint128::new(100)
+        );
+
+        // The owner withdraws the collected tokens
+        let withdraw = |amount: Option<u128>| ExecuteMsg::WithdrawCw20Fees {
+            token: "token".to_string(),
+            amount: amount.map(Uint128::new),
+            recipient: None,
+        };
+        assert!(matches!(
+            run(deps.as_mut(), "alice", &[], withdraw(None)),
+            Err(ContractError::Unauthorized {})
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "owner", &[], withdraw(Some(101))),
+            Err(ContractError::InsufficientFees {})
+        ));
+        let res = run(deps.as_mut(), "owner", &[], withdraw(None)).unwrap();
+        assert_eq!(
+            cw20_transfers(&res.messages),
+            vec![("token".to_string(), "owner".to_string(), Uint128::new(100))]
+        );
+        assert!(matches!(
+            run(deps.as_mut(), "owner", &[], withdraw(None)),
+            Err(ContractError::InsufficientFees {})
+        ));
+    }
+
+    #[test]
+    fn due_jobs_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        let frequent = register_job(deps.as_mut(), 2, &["BTC"]);
+        register_job(deps.as_mut(), 3, &["ETH"]);
+        let due_jobs = |deps: Deps, env: Env, limit: Option<u32>| -> Vec<DueJobResponse> {
+            from_binary(&query(deps, env, QueryMsg::DueJobs { limit }).unwrap()).unwrap()
+        };
+        let set_interval = |deps: DepsMut, sender: &str, job_id: &str, interval: Option<u64>| {
+            run(
+                deps,
+                sender,
+                &[],
+                ExecuteMsg::SetUpdateInterval {
+                    job_id: job_id.to_string(),
+                    interval,
+                },
+            )
+        };
+
+        // Only the owner may set the update interval of an existing job
+        assert!(matches!(
+            set_interval(deps.as_mut(), "alice", &job_id, Some(60)),
+            Err(ContractError::Unauthorized {})
+        ));
+        assert!(matches!(
+            set_interval(deps.as_mut(), "owner", "unknown", Some(60)),
+            Err(ContractError::JobNotFound {})
+        ));
+        set_interval(deps.as_mut(), "owner", &job_id, Some(60)).unwrap();
+        assert_eq!(due_jobs(deps.as_ref(), mock_env(), None).len(), 1);
+
+        // A job with a request in flight is not due, whatever its interval
+        let now = mock_env().block.time.seconds();
+        let client_id = update_job(deps.as_mut(), &job_id);
+        assert_eq!(due_jobs(deps.as_ref(), mock_env(), None), vec![]);
+        let status: JobStatus = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::JobStatus {
+                    job_id: job_id.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            status,
+            JobStatus {
+                last_request: Some(now),
+                last_success: None,
+            }
+        );
+
+        // Once updated, the job is due again after its interval, most overdue first
+        receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &client_id,
+                1,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[1000000]),
+            ),
+        );
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(59);
+        assert_eq!(due_jobs(deps.as_ref(), env.clone(), None), vec![]);
+        set_interval(deps.as_mut(), "owner", &frequent, Some(30)).unwrap();
+        env.block.time = env.block.time.plus_seconds(31);
+        assert_eq!(
+            due_jobs(deps.as_ref(), env.clone(), None),
+            vec![
+                DueJobResponse {
+                    job_id: frequent.clone(),
+                    overdue_by: now + 60,
+                },
+                DueJobResponse {
+                    job_id: job_id.clone(),
+                    overdue_by: 30,
+                },
+            ]
+        );
+        assert_eq!(due_jobs(deps.as_ref(), env.clone(), Some(1)).len(), 1);
+
+        // Clearing the interval stops scheduling the job
+        set_interval(deps.as_mut(), "owner", &frequent, None).unwrap();
+        assert_eq!(
+            due_jobs(deps.as_ref(), env, None),
+            vec![DueJobResponse {
+                job_id,
+                overdue_by: 30,
+            }]
+        );
+    }
+
+    #[test]
+    fn sudo_tick_test() {
+        let mut deps = setup();
+        let mut job_ids = vec![];
+        for oracle_script_id in 1..=3 {
+            let job_id = register_job(deps.as_mut(), oracle_script_id, &["BTC"]);
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                ExecuteMsg::SetUpdateInterval {
+                    job_id: job_id.clone(),
+                    interval: Some(60),
+                },
+            )
+            .unwrap();
+            job_ids.push(job_id);
+        }
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetKeeperReward {
+                keeper_reward: Some(coin(10, "uluna")),
+            },
+        )
+        .unwrap();
+        run(
+            deps.as_mut(),
+            "funder",
+            &coins(100, "uluna"),
+            ExecuteMsg::FundRewards {},
+        )
+        .unwrap();
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "alice",
+                &[],
+                ExecuteMsg::SetTickBudget { tick_budget: 2 }
+            ),
+            Err(ContractError::Unauthorized {})
+        ));
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetTickBudget { tick_budget: 2 },
+        )
+        .unwrap();
+
+        // Each tick requests the due jobs without a request in flight, up to the budget
+        let res = sudo(deps.as_mut(), mock_env(), SudoMsg::Tick {}).unwrap();
+        let packets = sent_packets(&res);
+        assert_eq!(packets.len(), 2);
+        assert_eq!(attribute(&res, "job_ids"), job_ids[..2].join(","));
+        let res = sudo(deps.as_mut(), mock_env(), SudoMsg::Tick {}).unwrap();
+        assert_eq!(attribute(&res, "job_ids"), job_ids[2]);
+        let res = sudo(deps.as_mut(), mock_env(), SudoMsg::Tick {}).unwrap();
+        assert_eq!(sent_packets(&res), vec![]);
+
+        // Requests sent by a tick earn no keeper reward
+        receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &packets[0].client_id,
+                1,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[1000000]),
+            ),
+        );
+        assert_eq!(
+            PENDING_REQUESTS
+                .may_load(&deps.storage, &job_ids[0])
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            REWARD_POOL.load(&deps.storage, "uluna").unwrap(),
+            Uint128::new(100)
+        );
+        assert_eq!(
+            KEEPER_REWARDS
+                .may_load(&deps.storage, (&mock_env().contract.address, "uluna"))
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn sudo_config_test() {
+        let mut deps = setup();
+        let config = |deps: Deps| -> ConfigResponse {
+            from_binary(&query(deps, mock_env(), QueryMsg::Config {}).unwrap()).unwrap()
+        };
+
+        // The chain may move the channel and the ownership without the owner
+        sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::SetChannel {
+                channel: "channel-1".to_string(),
+            },
+        )
+        .unwrap();
+        sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::SetOwner {
+                owner: "governance".to_string(),
+            },
+        )
+        .unwrap();
+        let config = config(deps.as_ref());
+        assert_eq!(config.channel, "channel-1");
+        assert_eq!(config.owner, "governance");
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                ExecuteMsg::SetTickBudget { tick_budget: 1 }
+            ),
+            Err(ContractError::Unauthorized {})
+        ));
+        run(
+            deps.as_mut(),
+            "governance",
+            &[],
+            ExecuteMsg::SetTickBudget { tick_budget: 1 },
+        )
+        .unwrap();
+
+        // Ticks need a channel to send the requests on
+        sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::SetChannel {
+                channel: String::new(),
+            },
+        )
+        .unwrap();
+        assert!(matches!(
+            sudo(deps.as_mut(), mock_env(), SudoMsg::Tick {}),
+            Err(ContractError::ChannelNotSet {})
+        ));
+    }
+
+    #[test]
+    fn symbol_update_test() {
+        let mut deps = setup();
+        let update_symbols =
+            |symbols: &[&str], oracle_script_id: Option<u64>| ExecuteMsg::UpdateSymbols {
+                symbols: symbols.iter().map(|symbol| symbol.to_string()).collect(),
+                oracle_script_id,
+            };
+        let default_request = |multiplier: u64| ExecuteMsg::SetDefaultRequest {
+            default_request: Some(DefaultRequest {
+                oracle_script_id: 1,
+                multiplier,
+                ask_count: 4,
+                min_count: 3,
+            }),
+        };
+
+        // Symbols can only be updated once the owner set the defaults and allowed them
+        assert!(matches!(
+            run(deps.as_mut(), "alice", &[], update_symbols(&["BTC"], None)),
+            Err(ContractError::DefaultRequestNotSet {})
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "alice", &[], default_request(1000000)),
+            Err(ContractError::Unauthorized {})
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "owner", &[], default_request(0)),
+            Err(ContractError::ZeroMultiplier {})
+        ));
+        run(deps.as_mut(), "owner", &[], default_request(1000000)).unwrap();
+        let allow = ExecuteMsg::SetAllowedSymbols {
+            symbols: vec!["BTC".to_string(), "ETH".to_string()],
+            allowed: true,
+        };
+        assert!(matches!(
+            run(deps.as_mut(), "alice", &[], allow.clone()),
+            Err(ContractError::Unauthorized {})
+        ));
+        run(deps.as_mut(), "owner", &[], allow).unwrap();
+        assert!(matches!(
+            run(deps.as_mut(), "alice", &[], update_symbols(&[], None)),
+            Err(ContractError::EmptySymbols {})
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "alice", &[], update_symbols(&["BTC", "LUNA"], None)),
+            Err(ContractError::SymbolNotAllowed { symbol }) if symbol == "LUNA"
+        ));
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "alice",
+                &[],
+                update_symbols(&["BTC"], Some(2))
+            ),
+            Err(ContractError::Unauthorized {})
+        ));
+
+        // The response is stored as the symbol request source and published
+        let res = run(deps.as_mut(), "alice", &[], update_symbols(&["BTC"], None)).unwrap();
+        let request = sent_packets(&res).remove(0);
+        assert_eq!(request.oracle_script_id, 1);
+        assert_eq!((request.ask_count, request.min_count), (4, 3));
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &request.client_id,
+                1,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[20000000000]),
+            ),
+        );
+        assert!(res.attributes.contains(&attr("symbols", "BTC")));
+        assert_eq!(
+            price(deps.as_ref(), mock_env(), "BTC").unwrap().rate,
+            Decimal::from_ratio(20000u64, 1u64)
+        );
+        assert_eq!(
+            SOURCE_PRICES
+                .load(&deps.storage, ("BTC", SYMBOL_REQUEST_SOURCE))
+                .unwrap()
+                .bandchain_request_id,
+            1
+        );
+        assert!(!SYMBOL_REQUESTS.has(&deps.storage, &request.client_id));
+
+        // Anyone but the owner has to wait for the cooldown before requesting a symbol again
+        let now = mock_env().block.time.seconds();
+        assert!(matches!(
+            run(deps.as_mut(), "bob", &[], update_symbols(&["ETH", "BTC"], None)),
+            Err(ContractError::SymbolRequestedRecently { symbol, retry_at })
+                if symbol == "BTC" && retry_at == now + SYMBOL_REQUEST_COOLDOWN
+        ));
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            update_symbols(&["BTC"], Some(2)),
+        )
+        .unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(SYMBOL_REQUEST_COOLDOWN);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("bob", &[]),
+            update_symbols(&["ETH", "BTC"], None),
+        )
+        .unwrap();
+
+        // A timed out request is dropped without touching the prices
+        let request = sent_packets(&res).remove(0);
+        ibc_packet_timeout(
+            deps.as_mut(),
+            mock_env(),
+            mock_ibc_packet_timeout(CHANNEL, &request).unwrap(),
+        )
+        .unwrap();
+        assert!(!SYMBOL_REQUESTS.has(&deps.storage, &request.client_id));
+        assert_eq!(
+            price(deps.as_ref(), mock_env(), "BTC").unwrap().rate,
+            Decimal::from_ratio(20000u64, 1u64)
+        );
+    }
+
+    #[test]
+    fn follow_up_test() {
+        let mut deps = setup();
+        let job_ids: Vec<String> = (1..=5)
+            .map(|oracle_script_id| register_job(deps.as_mut(), oracle_script_id, &["BTC"]))
+            .collect();
+        let set_follow_ups = |job_id: &str, follow_ups: &[&String]| ExecuteMsg::SetFollowUps {
+            job_id: job_id.to_string(),
+            follow_ups: follow_ups.iter().map(|job_id| job_id.to_string()).collect(),
+        };
+        for pair in job_ids.windows(2) {
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                set_follow_ups(&pair[0], &[&pair[1]]),
+            )
+            .unwrap();
+        }
+
+        // Follow-ups must be registered jobs that never lead back to the job
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "alice",
+                &[],
+                set_follow_ups(&job_ids[4], &[&job_ids[0]])
+            ),
+            Err(ContractError::Unauthorized {})
+        ));
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                set_follow_ups(&job_ids[4], &[&"tvl-9".to_string()])
+            ),
+            Err(ContractError::JobNotFound {})
+        ));
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                set_follow_ups(&job_ids[4], &[&job_ids[4]])
+            ),
+            Err(ContractError::FollowUpCycle { job_id }) if job_id == job_ids[4]
+        ));
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                set_follow_ups(&job_ids[4], &[&job_ids[2]])
+            ),
+            Err(ContractError::FollowUpCycle { job_id }) if job_id == job_ids[4]
+        ));
+
+        // Each successful update requests the next job of the chain, up to the maximum depth
+        let mut client_id = update_job(deps.as_mut(), &job_ids[0]);
+        for (index, job_id) in job_ids.iter().enumerate().take(4) {
+            let res = receive(
+                deps.as_mut(),
+                mock_env(),
+                &response_packet(
+                    &client_id,
+                    index as u64 + 1,
+                    "RESOLVE_STATUS_SUCCESS",
+                    price_result(&[1000000]),
+                ),
+            );
+            assert!(!PENDING_REQUESTS.has(&deps.storage, job_id));
+            if index as u32 == MAX_FOLLOW_UP_DEPTH {
+                assert!(res.attributes.contains(&attr("follow_ups", "")));
+                assert!(!PENDING_REQUESTS.has(&deps.storage, &job_ids[index + 1]));
+                continue;
+            }
+            assert!(res
+                .attributes
+                .contains(&attr("follow_ups", job_ids[index + 1].as_str())));
+            let pending = PENDING_REQUESTS
+                .load(&deps.storage, &job_ids[index + 1])
+                .unwrap();
+            assert_eq!(pending.requester, mock_env().contract.address);
+            assert_eq!(pending.depth, index as u32 + 1);
+            client_id = pending.client_id;
+        }
+
+        // A failed update requests no follow-up
+        let client_id = update_job(deps.as_mut(), &job_ids[3]);
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(&client_id, 5, "RESOLVE_STATUS_FAILURE", String::new()),
+        );
+        assert_eq!(res.messages, vec![]);
+        assert!(!PENDING_REQUESTS.has(&deps.storage, &job_ids[4]));
+    }
+
+    #[test]
+    fn job_history_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        let history = |deps: Deps, start_after: Option<u64>| -> Vec<JobHistoryEntry> {
+            from_binary(
+                &query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::JobHistory {
+                        job_id: "tvl-1".to_string(),
+                        start_after,
+                        limit: None,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        let stats = |deps: Deps| -> JobStatsResponse {
+            from_binary(
+                &query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::JobStats {
+                        job_id: "tvl-1".to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+
+        // The latency of a resolved request is measured up to its resolve time on BandChain
+        let now = mock_env().block.time.seconds();
+        let mut packet = response_packet(
+            &update_job(deps.as_mut(), &job_id),
+            7,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[1000000]),
+        );
+        packet.resolve_time = (now + 10).to_string();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(30);
+        receive(deps.as_mut(), env, &packet);
+        assert_eq!(
+            history(deps.as_ref(), None),
+            vec![JobHistoryEntry {
+                sequence: 0,
+                sent_at: now,
+                bandchain_request_id: Some(7),
+                outcome: RequestOutcome::Success,
+                latency: 10,
+            }]
+        );
+
+        // Unresolved, expired, timed out, rejected and replaced requests are told apart
+        for resolve_status in ["RESOLVE_STATUS_FAILURE", "RESOLVE_STATUS_EXPIRED"] {
+            let packet = response_packet(
+                &update_job(deps.as_mut(), &job_id),
+                8,
+                resolve_status,
+                String::new(),
+            );
+            receive(deps.as_mut(), mock_env(), &packet);
+        }
+        let res = run(
+            deps.as_mut(),
+            "keeper",
+            &[],
+            ExecuteMsg::UpdateJobData {
+                job_id: job_id.clone(),
+            },
+        )
+        .unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(20);
+        ibc_packet_timeout(
+            deps.as_mut(),
+            env,
+            mock_ibc_packet_timeout(CHANNEL, &sent_packets(&res)[0]).unwrap(),
+        )
+        .unwrap();
+        let res = run(
+            deps.as_mut(),
+            "keeper",
+            &[],
+            ExecuteMsg::UpdateJobData {
+                job_id: job_id.clone(),
+            },
+        )
+        .unwrap();
+        ibc_packet_ack(
+            deps.as_mut(),
+            mock_env(),
+            mock_ibc_packet_ack(
+                CHANNEL,
+                &sent_packets(&res)[0],
+                IbcAcknowledgement::new(make_ack_fail("invalid calldata".to_string())),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        update_job(deps.as_mut(), &job_id);
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(PENDING_REQUEST_EXPIRY);
+        let client_id = update_job_at(deps.as_mut(), env, &job_id);
+        let entries = history(deps.as_ref(), Some(0));
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.sequence, entry.outcome.clone(), entry.latency))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, RequestOutcome::Failure, 0),
+                (2, RequestOutcome::Expired, 0),
+                (3, RequestOutcome::Timeout, 20),
+                (4, RequestOutcome::Rejected, 0),
+                (5, RequestOutcome::Replaced, PENDING_REQUEST_EXPIRY),
+            ]
+        );
+        assert_eq!(entries[1].bandchain_request_id, Some(8));
+        assert_eq!(entries[2].bandchain_request_id, None);
+        assert_eq!(
+            stats(deps.as_ref()),
+            JobStatsResponse {
+                stats: JobStats {
+                    requests: 7,
+                    successes: 1,
+                    failures: 1,
+                    expired: 1,
+                    timeouts: 1,
+                    rejected: 1,
+                    replaced: 1,
+                    total_latency: 30 + PENDING_REQUEST_EXPIRY,
+                },
+                average_latency: (30 + PENDING_REQUEST_EXPIRY) / 6,
+            }
+        );
+
+        // Only the latest requests are kept
+        let packet = response_packet(&client_id, 9, "RESOLVE_STATUS_FAILURE", String::new());
+        receive(deps.as_mut(), mock_env(), &packet);
+        for _ in 0..MAX_JOB_HISTORY {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(PENDING_REQUEST_EXPIRY);
+            let packet = response_packet(
+                &update_job_at(deps.as_mut(), env.clone(), &job_id),
+                9,
+                "RESOLVE_STATUS_FAILURE",
+                String::new(),
+            );
+            receive(deps.as_mut(), env, &packet);
+        }
+        assert_eq!(history(deps.as_ref(), None)[0].sequence, 7);
+        assert!(!JOB_HISTORY.has(&deps.storage, (&job_id, 6)));
+        assert_eq!(stats(deps.as_ref()).stats.failures, 2 + MAX_JOB_HISTORY);
+    }
+
+    #[test]
+    fn provenance_test() {
+        let mut deps = setup();
+        let primary = register_job(deps.as_mut(), 1, &["BTC"]);
+        let secondary = register_job(deps.as_mut(), 2, &["BTC"]);
+        let bandchain_request = |deps: Deps, request_id: u64| {
+            query(deps, mock_env(), QueryMsg::BandchainRequest { request_id })
+                .map(|res| from_binary::<BandchainRequest>(&res).unwrap())
+        };
+        let resolve = |deps: DepsMut, client_id: &str, request_id: u64, rate: u64| {
+            let packet = response_packet(
+                client_id,
+                request_id,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[rate]),
+            );
+            receive(deps, mock_env(), &packet);
+        };
+
+        // A response is indexed as received, along with its raw result
+        let client_id = update_job(deps.as_mut(), &primary);
+        resolve(deps.as_mut(), &client_id, 1, 20000000000);
+        let request = bandchain_request(deps.as_ref(), 1).unwrap();
+        assert_eq!(request.client_id, client_id);
+        assert_eq!(request.job_ids, vec![primary.clone()]);
+        assert_eq!(request.symbols, vec!["BTC".to_string()]);
+        assert_eq!(request.channel, CHANNEL);
+        assert_eq!(request.result, price_result(&[20000000000]));
+
+        // Responses that produce no stored price are not kept
+        let client_id = update_job(deps.as_mut(), &secondary);
+        let packet = response_packet(&client_id, 2, "RESOLVE_STATUS_FAILURE", String::new());
+        receive(deps.as_mut(), mock_env(), &packet);
+        assert!(bandchain_request(deps.as_ref(), 2).is_err());
+        resolve(deps.as_mut(), "tvl-9:1", 3, 1);
+        assert!(bandchain_request(deps.as_ref(), 3).is_err());
+
+        // A response is kept while any source price still comes from it
+        let client_id = update_job(deps.as_mut(), &secondary);
+        resolve(deps.as_mut(), &client_id, 4, 20100000000);
+        let provenance: ProvenanceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Provenance {
+                    symbol: "BTC".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(provenance.price.bandchain_request_id, 4);
+        assert_eq!(provenance.request.unwrap().client_id, client_id);
+        assert_eq!(
+            provenance
+                .sources
+                .iter()
+                .map(|source| (
+                    source.source.as_str(),
+                    source
+                        .request
+                        .as_ref()
+                        .map(|request| request.client_id.as_str())
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (primary.as_str(), Some(format!("{}:1", primary).as_str())),
+                (secondary.as_str(), Some(client_id.as_str())),
+            ]
+        );
+
+        // And pruned once no stored price comes from it anymore
+        let client_id = update_job(deps.as_mut(), &primary);
+        resolve(deps.as_mut(), &client_id, 5, 20200000000);
+        assert!(bandchain_request(deps.as_ref(), 1).is_err());
+        assert!(bandchain_request(deps.as_ref(), 4).is_ok());
+        assert!(bandchain_request(deps.as_ref(), 5).is_ok());
+    }
+
+    #[test]
+    fn oracle_script_test() {
+        let mut deps = setup();
+        let oracle_script = |schema: &str, kind: OracleScriptKind| OracleScript {
+            schema: schema.to_string(),
+            kind,
+            ask_count: 16,
+            min_count: 10,
+            fee_limit: coins(10, "uband"),
+            prepare_gas: 100000,
+            execute_gas: 200000,
+        };
+        let set =
+            |deps: DepsMut, sender: &str, oracle_script_id: u64, oracle_script: OracleScript| {
+                run(
+                    deps,
+                    sender,
+                    &[],
+                    ExecuteMsg::SetOracleScript {
+                        oracle_script_id,
+                        oracle_script,
+                    },
+                )
+            };
+        let register = |deps: DepsMut,
+                        oracle_script_id: u64,
+                        ask_count: Option<u64>,
+                        min_count: Option<u64>| {
+            run(
+                deps,
+                "owner",
+                &[],
+                ExecuteMsg::RegisterJob {
+                    oracle_script_id,
+                    symbols: vec!["BTC".to_string()],
+                    multiplier: 1000000,
+                    ask_count,
+                    min_count,
+                    update_interval: None,
+                    calldata_variant: None,
+                },
+            )
+            .map(|res| attribute(&res, "job_id"))
+        };
+        let price_feed = "{symbols:[string],multiplier:u64}/{rates:[u64]}";
+
+        // Only the owner registers scripts, whose schema and defaults must be valid
+        assert!(matches!(
+            set(
+                deps.as_mut(),
+                "alice",
+                1,
+                oracle_script(price_feed, OracleScriptKind::PriceFeed)
+            ),
+            Err(ContractError::Unauthorized {})
+        ));
+        assert!(matches!(
+            set(
+                deps.as_mut(),
+                "owner",
+                1,
+                oracle_script("{symbols:[string]", OracleScriptKind::PriceFeed)
+            ),
+            Err(ContractError::InvalidObiSchema { .. })
+        ));
+        assert!(matches!(
+            set(
+                deps.as_mut(),
+                "owner",
+                1,
+                oracle_script(
+                    "{symbols:[string],multiplier:u64}/{rates:[string]}",
+                    OracleScriptKind::PriceFeed
+                )
+            ),
+            Err(ContractError::InvalidObiSchema { .. })
+        ));
+        for (ask_count, min_count) in [(16, 0), (16, 17)] {
+            assert!(matches!(
+                set(
+                    deps.as_mut(),
+                    "owner",
+                    1,
+                    OracleScript {
+                        ask_count,
+                        min_count,
+                        ..oracle_script(price_feed, OracleScriptKind::PriceFeed)
+                    }
+                ),
+                Err(ContractError::InvalidRequestParams {})
+            ));
+        }
+        set(
+            deps.as_mut(),
+            "owner",
+            1,
+            oracle_script(price_feed, OracleScriptKind::PriceFeed),
+        )
+        .unwrap();
+        set(
+            deps.as_mut(),
+            "owner",
+            2,
+            oracle_script(
+                "{symbols:[string]}/{rates:[u64]}",
+                OracleScriptKind::PriceFeed,
+            ),
+        )
+        .unwrap();
+        set(
+            deps.as_mut(),
+            "owner",
+            3,
+            oracle_script("{query:string}/{answer:string}", OracleScriptKind::Generic),
+        )
+        .unwrap();
+        let scripts: Vec<OracleScriptResponse> = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::OracleScripts {
+                    start_after: Some(1),
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            scripts
+                .iter()
+                .map(|script| script.oracle_script_id)
+                .collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+
+        // Omitted request parameters default to the registered script's
+        let job_id = register(deps.as_mut(), 1, None, Some(4)).unwrap();
+        let job = JOBS.load(&deps.storage, &job_id).unwrap();
+        assert_eq!((job.ask_count, job.min_count), (16, 4));
+        let job_id = register(deps.as_mut(), 1, None, None).unwrap();
+        let job = JOBS.load(&deps.storage, &job_id).unwrap();
+        assert_eq!((job.ask_count, job.min_count), (16, 10));
+
+        // Explicit and defaulted pairs alike must be consistent
+        for (ask_count, min_count) in [
+            (Some(4), Some(0)),
+            (Some(4), Some(5)),
+            (Some(4), None),
+            (None, Some(17)),
+        ] {
+            assert!(matches!(
+                register(deps.as_mut(), 1, ask_count, min_count),
+                Err(ContractError::InvalidRequestParams {})
+            ));
+        }
+        assert!(matches!(
+            register(deps.as_mut(), 9, Some(4), Some(0)),
+            Err(ContractError::InvalidRequestParams {})
+        ));
+
+        // Calldata must match the schema and kind of the script
+        assert!(matches!(
+            register(deps.as_mut(), 2, None, None),
+            Err(ContractError::InvalidCalldata { .. })
+        ));
+        assert!(matches!(
+            register(deps.as_mut(), 3, None, None),
+            Err(ContractError::WrongOracleScriptKind {
+                oracle_script_id: 3
+            })
+        ));
+
+        // Unregistered scripts need explicit parameters
+        assert!(matches!(
+            register(deps.as_mut(), 9, Some(4), None),
+            Err(ContractError::MissingRequestParams {
+                oracle_script_id: 9
+            })
+        ));
+        register(deps.as_mut(), 9, Some(4), Some(3)).unwrap();
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "alice",
+                &[],
+                ExecuteMsg::RemoveOracleScript {
+                    oracle_script_id: 1
+                }
+            ),
+            Err(ContractError::Unauthorized {})
+        ));
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::RemoveOracleScript {
+                oracle_script_id: 1,
+            },
+        )
+        .unwrap();
+        assert!(matches!(
+            register(deps.as_mut(), 1, None, None),
+            Err(ContractError::MissingRequestParams {
+                oracle_script_id: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn generic_job_test() {
+        let mut deps = setup();
+        let schema = "{team:string}/{home:u8,away:u8,final:bool}";
+        let oracle_script = |schema: &str, kind: OracleScriptKind| OracleScript {
+            schema: schema.to_string(),
+            kind,
+            ask_count: 16,
+            min_count: 10,
+            fee_limit: coins(10, "uband"),
+            prepare_gas: 100000,
+            execute_gas: 200000,
+        };
+        for (oracle_script_id, oracle_script) in [
+            (7, oracle_script(schema, OracleScriptKind::Generic)),
+            (
+                8,
+                oracle_script(
+                    "{symbols:[string],multiplier:u64}/{rates:[u64]}",
+                    OracleScriptKind::PriceFeed,
+                ),
+            ),
+        ] {
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                ExecuteMsg::SetOracleScript {
+                    oracle_script_id,
+                    oracle_script,
+                },
+            )
+            .unwrap();
+        }
+        let register =
+            |deps: DepsMut, oracle_script_id: u64, schema: Option<&str>, calldata: &str| {
+                run(
+                    deps,
+                    "owner",
+                    &[],
+                    ExecuteMsg::RegisterGenericJob {
+                        oracle_script_id,
+                        schema: schema.map(|schema| schema.to_string()),
+                        calldata: calldata.to_string(),
+                        ask_count: None,
+                        min_count: None,
+                        update_interval: None,
+                    },
+                )
+                .map(|res| attribute(&res, "job_id"))
+            };
+        let job_result = |deps: Deps, job_id: &str| -> StdResult<JobResult> {
+            from_binary(&query(
+                deps,
+                mock_env(),
+                QueryMsg::JobResult {
+                    job_id: job_id.to_string(),
+                },
+            )?)
+        };
+
+        // The calldata must match the schema and kind of the script, and unregistered scripts need a schema
+        assert!(matches!(
+            register(deps.as_mut(), 7, None, r#"{"team":1}"#),
+            Err(ContractError::InvalidCalldata { .. })
+        ));
+        assert!(matches!(
+            register(deps.as_mut(), 7, None, r#"{"team":"LAL""#),
+            Err(ContractError::InvalidJson { .. })
+        ));
+        assert!(matches!(
+            register(deps.as_mut(), 8, None, r#"{"team":"LAL"}"#),
+            Err(ContractError::WrongOracleScriptKind {
+                oracle_script_id: 8
+            })
+        ));
+        assert!(matches!(
+            register(deps.as_mut(), 9, None, r#"{"team":"LAL"}"#),
+            Err(ContractError::MissingSchema {
+                oracle_script_id: 9
+            })
+        ));
+
+        // Generic jobs have no symbols nor multiplier, and request with the script's parameters
+        let job_id = register(deps.as_mut(), 7, None, r#"{"team":"LAL"}"#).unwrap();
+        let job = JOBS.load(&deps.storage, &job_id).unwrap();
+        assert_eq!(job.symbols, Vec::<String>::new());
+        assert_eq!(job.multiplier, None);
+        assert_eq!(job.schema, Some(schema.to_string()));
+        assert_eq!((job.ask_count, job.min_count), (16, 10));
+        let price_job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        let res = run(
+            deps.as_mut(),
+            "keeper",
+            &[],
+            ExecuteMsg::UpdateJobsData {
+                job_ids: vec![job_id.clone(), price_job_id.clone()],
+                merge: true,
+            },
+        )
+        .unwrap();
+        let requests = sent_packets(&res);
+        assert_eq!(requests.len(), 2);
+        let request = requests
+            .into_iter()
+            .find(|request| request.oracle_script_id == 7)
+            .unwrap();
+        assert_eq!(request.calldata, b"\0\0\0\x03LAL".to_vec());
+        assert_eq!(request.fee_limit, coins(10, "uband"));
+        assert_eq!((request.prepare_gas, request.execute_gas), (100000, 200000));
+
+        // A malformed result fails the ack and stores nothing
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &request.client_id,
+                1,
+                "RESOLVE_STATUS_SUCCESS",
+                base64::encode([102, 99]),
+            ),
+        );
+        assert_ne!(res.acknowledgement, make_ack_success());
+        assert!(job_result(deps.as_ref(), &job_id).is_err());
+
+        // A successful result is stored as JSON, without touching the price tables
+        let client_id = update_job(deps.as_mut(), &job_id);
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &client_id,
+                2,
+                "RESOLVE_STATUS_SUCCESS",
+                base64::encode([102, 99, 1]),
+            ),
+        );
+        assert_eq!(res.acknowledgement, make_ack_success());
+        let expected = JobResult {
+            result: r#"{"home":102,"away":99,"final":true}"#.to_string(),
+            bandchain_request_id: 2,
+            bandchain_resolve_time: mock_env().block.time.seconds(),
+        };
+        assert_eq!(job_result(deps.as_ref(), &job_id).unwrap(), expected);
+        assert!(!PENDING_REQUESTS.has(&deps.storage, &job_id));
+        assert!(JOB_STATUS
+            .load(&deps.storage, &job_id)
+            .unwrap()
+            .last_success
+            .is_some());
+        assert_eq!(JOB_STATS.load(&deps.storage, &job_id).unwrap().successes, 1);
+        assert!(PRICES
+            .range(&deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_none());
+        assert!(SOURCE_PRICES
+            .prefix(&job_id)
+            .range(&deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_none());
+
+        // The raw result can be reprocessed into the same JSON result
+        JOB_RESULTS.remove(&mut deps.storage, &job_id);
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::ReprocessResult {
+                job_id: job_id.clone(),
+                request_id: 2,
+            },
+        )
+        .unwrap();
+        assert_eq!(job_result(deps.as_ref(), &job_id).unwrap(), expected);
+
+        // Price jobs still need a multiplier
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                ExecuteMsg::RegisterJob {
+                    oracle_script_id: 1,
+                    symbols: vec!["BTC".to_string()],
+                    multiplier: 0,
+                    ask_count: Some(4),
+                    min_count: Some(3),
+                    update_interval: None,
+                    calldata_variant: None,
+                }
+            ),
+            Err(ContractError::ZeroMultiplier {})
+        ));
+    }
+
+    #[test]
+    fn randomness_test() {
+        let mut deps = setup();
+        let request = |deps: DepsMut, seed: &[u8]| {
+            run(
+                deps,
+                "dice",
+                &[],
+                ExecuteMsg::RequestRandomness {
+                    seed: Binary::from(seed),
+                },
+            )
+        };
+        let randomness = |deps: Deps, request_id: &str| -> Randomness {
+            from_binary(
+                &query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::Randomness {
+                        request_id: request_id.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        let callback = |res: &IbcReceiveResponse| -> RequesterExecuteMsg {
+            match &res.messages[0].msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) => {
+                    assert_eq!(contract_addr, "dice");
+                    from_binary(msg).unwrap()
+                }
+                _ => panic!("expected a requester callback"),
+            }
+        };
+
+        // Only allowed requesters may request randomness, once the VRF parameters are set
+        assert!(matches!(
+            request(deps.as_mut(), b"seed"),
+            Err(ContractError::Unauthorized {})
+        ));
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetRequester {
+                address: "dice".to_string(),
+                allowed: true,
+            },
+        )
+        .unwrap();
+        assert!(matches!(
+            request(deps.as_mut(), b"seed"),
+            Err(ContractError::VrfRequestNotSet {})
+        ));
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetVrfRequest {
+                vrf_request: Some(VrfRequest {
+                    oracle_script_id: 5,
+                    ask_count: 4,
+                    min_count: 3,
+                }),
+            },
+        )
+        .unwrap();
+        assert!(matches!(
+            request(deps.as_mut(), b""),
+            Err(ContractError::EmptySeed {})
+        ));
+
+        // The resolved randomness is stored and delivered to the requester
+        let res = request(deps.as_mut(), b"seed").unwrap();
+        let client_id = attribute(&res, "client_id");
+        let packet = sent_packets(&res).remove(0);
+        assert_eq!(packet.oracle_script_id, 5);
+        assert_eq!(
+            packet.calldata,
+            VrfInput {
+                seed: b"seed".to_vec(),
+                time: mock_env().block.time.seconds(),
+                worker_contract: "dice".to_string(),
+            }
+            .encode_obi()
+            .unwrap()
+        );
+        assert_eq!(
+            randomness(deps.as_ref(), &client_id).status,
+            RandomnessStatus::Pending
+        );
+        let response = response_packet(
+            &client_id,
+            1,
+            "RESOLVE_STATUS_SUCCESS",
+            "AAAAAgECAAAAAQM=".to_string(),
+        );
+        let res = receive(deps.as_mut(), mock_env(), &response);
+        assert_eq!(res.acknowledgement, make_ack_success());
+        assert_eq!(
+            callback(&res),
+            RequesterExecuteMsg::ReceiveRandomness {
+                request_id: client_id.clone(),
+                hash: Binary::from(vec![3]),
+                proof: Binary::from(vec![1, 2]),
+            }
+        );
+        assert!(matches!(
+            randomness(deps.as_ref(), &client_id).status,
+            RandomnessStatus::Fulfilled {
+                bandchain_request_id: 1,
+                ..
+            }
+        ));
+
+        // A second response to the same request is stale
+        let res = receive(deps.as_mut(), mock_env(), &response);
+        assert!(res.attributes.contains(&attr("error_code", "stale")));
+        assert!(res.messages.is_empty());
+
+        // A request that times out fails and notifies the requester
+        let res = request(deps.as_mut(), b"seed").unwrap();
+        let client_id = attribute(&res, "client_id");
+        let packet = sent_packets(&res).remove(0);
+        let res = ibc_packet_timeout(
+            deps.as_mut(),
+            mock_env(),
+            mock_ibc_packet_timeout(CHANNEL, &packet).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert!(matches!(
+            randomness(deps.as_ref(), &client_id).status,
+            RandomnessStatus::Failed { .. }
+        ));
+    }
 }
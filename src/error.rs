@@ -24,6 +24,122 @@ pub enum ContractError {
     #[error("Provided job id is not registered")]
     JobNotFound {},
 
+    #[error("Aggregation requires at least one source")]
+    InvalidAggregation {},
+
+    #[error("Job {job_id} does not report a price for {symbol}")]
+    SymbolNotCovered { job_id: String, symbol: String },
+
+    #[error("Price override must expire in the future")]
+    InvalidOverrideExpiry {},
+
+    #[error("Price override cannot last longer than {max_duration} seconds")]
+    OverrideTooLong { max_duration: u64 },
+
+    #[error("Only contracts can subscribe to price updates")]
+    SubscriberNotContract {},
+
+    #[error("Symbol {symbol} already has the maximum number of subscribers")]
+    TooManySubscribers { symbol: String },
+
+    #[error("Request must contain at least one symbol")]
+    EmptySymbols {},
+
+    #[error("Multiplier must be greater than zero")]
+    ZeroMultiplier {},
+
+    #[error("Cannot scale rate {rate} by multiplier {multiplier}")]
+    InvalidRate { rate: u64, multiplier: u64 },
+
+    #[error("Symbol {symbol} is not allowed to be updated without a job")]
+    SymbolNotAllowed { symbol: String },
+
+    #[error("Default request parameters are not set")]
+    DefaultRequestNotSet {},
+
+    #[error("No raw result stored for request {request_id} of job {job_id}")]
+    ResultNotFound { job_id: String, request_id: u64 },
+
+    #[error("A newer result of job {job_id} was already stored")]
+    StaleResult { job_id: String },
+
+    #[error("Invalid base64 result: {reason}")]
+    InvalidBase64 { reason: String },
+
+    #[error("Failed to decode OBI result: {reason}")]
+    ObiDecode { reason: String },
+
+    #[error("OBI result has {count} trailing bytes")]
+    TrailingBytes { count: usize },
+
+    #[error("Invalid JSON: {reason}")]
+    InvalidJson { reason: String },
+
+    #[error("Invalid OBI schema: {reason}")]
+    InvalidObiSchema { reason: String },
+
+    #[error("Failed to encode OBI calldata: {reason}")]
+    ObiEncode { reason: String },
+
+    #[error("Expected a non-zero multiplier for each symbol")]
+    InvalidMultipliers {},
+
+    #[error("VRF request parameters are not set")]
+    VrfRequestNotSet {},
+
+    #[error("Randomness seed cannot be empty")]
+    EmptySeed {},
+
+    #[error("Calldata does not match the oracle script schema: {reason}")]
+    InvalidCalldata { reason: String },
+
+    #[error("Oracle script {oracle_script_id} cannot be used for this kind of job")]
+    WrongOracleScriptKind { oracle_script_id: u64 },
+
+    #[error("Oracle script {oracle_script_id} is not registered, so its schema is required")]
+    MissingSchema { oracle_script_id: u64 },
+
+    #[error("Job {job_id} is not a price job")]
+    NotPriceJob { job_id: String },
+
+    #[error(
+        "Oracle script {oracle_script_id} is not registered, so ask and min count are required"
+    )]
+    MissingRequestParams { oracle_script_id: u64 },
+
+    #[error("Min count must be between 1 and the ask count")]
+    InvalidRequestParams {},
+
+    #[error("Invalid number in field {field}: {value}")]
+    InvalidNumber { field: String, value: String },
+
+    #[error("Symbol {symbol} was requested recently, retry at {retry_at}")]
+    SymbolRequestedRecently { symbol: String, retry_at: u64 },
+
+    #[error("Follow-up jobs of {job_id} would lead back to it")]
+    FollowUpCycle { job_id: String },
+
+    #[error("Invalid funds attached. Expected ({expected}).")]
+    InvalidFunds { expected: String },
+
+    #[error("Job {job_id} already has a request in flight")]
+    RequestInFlight { job_id: String },
+
+    #[error("Token is not accepted as payment")]
+    UnsupportedToken {},
+
+    #[error("No funds attached")]
+    NoFunds {},
+
+    #[error("No rewards to claim")]
+    NoRewards {},
+
+    #[error("Insufficient fees collected")]
+    InsufficientFees {},
+
+    #[error("This message does not accept funds")]
+    NonPayable {},
+
     #[error("Only unordered channels are supported.")]
     OrderedChannel {},
 
@@ -0,0 +1,119 @@
+use cosmwasm_std::{StdResult, Storage};
+
+use crate::state::{
+    JobHistoryEntry, JobStats, PendingRequest, RequestOutcome, SymbolFailures, JOB_HISTORY,
+    JOB_STATS, SYMBOL_FAILURES,
+};
+
+/// The number of finished requests kept in the history of each job
+pub const MAX_JOB_HISTORY: u64 = 50;
+
+/// ## Description
+/// Counts a new request sent for the specified job.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **job_id** is the ID of the job the request was sent for.
+pub fn record_request_sent(storage: &mut dyn Storage, job_id: &str) -> StdResult<()> {
+    JOB_STATS.update(storage, job_id, |stats| -> StdResult<_> {
+        let mut stats: JobStats = stats.unwrap_or_default();
+        stats.requests += 1;
+        Ok(stats)
+    })?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Appends a finished request to the history of the specified job and updates its counters.
+/// Only the latest [`MAX_JOB_HISTORY`] requests are kept.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **job_id** is the ID of the job the request was sent for.
+///
+/// - **pending** is an object of type [`PendingRequest`] which is the finished request.
+///
+/// - **outcome** is an object of type [`RequestOutcome`] which is how the request ended.
+///
+/// - **bandchain_request_id** is an [`Option`] of type [`u64`] which is the BandChain request ID, if known.
+///
+/// - **resolve_time** is an [`Option`] of type [`u64`] which is the time the request was resolved
+///   on BandChain, if it was. The latency is measured up to it, or up to now otherwise.
+///
+/// - **now** is the current block time in seconds.
+pub fn record_request_outcome(
+    storage: &mut dyn Storage,
+    job_id: &str,
+    pending: &PendingRequest,
+    outcome: RequestOutcome,
+    bandchain_request_id: Option<u64>,
+    resolve_time: Option<u64>,
+    now: u64,
+) -> StdResult<()> {
+    let mut stats = JOB_STATS.may_load(storage, job_id)?.unwrap_or_default();
+    let sequence = stats.finished();
+    let latency = resolve_time.unwrap_or(now).saturating_sub(pending.sent_at);
+
+    match outcome {
+        RequestOutcome::Success => stats.successes += 1,
+        RequestOutcome::Failure => stats.failures += 1,
+        RequestOutcome::Expired => stats.expired += 1,
+        RequestOutcome::Timeout => stats.timeouts += 1,
+        RequestOutcome::Rejected => stats.rejected += 1,
+        RequestOutcome::Replaced => stats.replaced += 1,
+    }
+    stats.total_latency += latency;
+    JOB_STATS.save(storage, job_id, &stats)?;
+
+    JOB_HISTORY.save(
+        storage,
+        (job_id, sequence),
+        &JobHistoryEntry {
+            sequence,
+            sent_at: pending.sent_at,
+            bandchain_request_id,
+            outcome,
+            latency,
+        },
+    )?;
+    if sequence >= MAX_JOB_HISTORY {
+        JOB_HISTORY.remove(storage, (job_id, sequence - MAX_JOB_HISTORY));
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Updates the failure counters of a symbol received in a successful response.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **symbol** is the symbol of the asset.
+///
+/// - **available** is whether the response held a usable rate for the symbol.
+///
+/// - **now** is the current block time in seconds.
+pub fn record_symbol_outcome(
+    storage: &mut dyn Storage,
+    symbol: &str,
+    available: bool,
+    now: u64,
+) -> StdResult<()> {
+    let mut failures = match SYMBOL_FAILURES.may_load(storage, symbol)? {
+        Some(failures) => failures,
+        None if available => return Ok(()),
+        None => SymbolFailures::default(),
+    };
+    if available {
+        failures.consecutive_failures = 0;
+    } else {
+        failures.failures += 1;
+        failures.consecutive_failures += 1;
+        failures.last_failure = Some(now);
+    }
+    SYMBOL_FAILURES.save(storage, symbol, &failures)
+}
@@ -1,21 +1,45 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Binary, Decimal, DepsMut, Env, IbcBasicResponse, IbcChannel,
-    IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcOrder, IbcPacketAckMsg,
-    IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse,
+    attr, from_binary, to_binary, to_vec, Addr, Binary, CosmosMsg, Decimal, DepsMut, Env,
+    IbcBasicResponse, IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
+    IbcOrder, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, Order,
+    StdError, StdResult, Storage, SubMsg, WasmMsg,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
+use crate::aggregation::aggregate_price;
+use crate::contract::{
+    client_job_id, follow_up_requests, job_due_at, load_oracle_price, reindex_due_job,
+    DEFAULT_RESULT_RETENTION, SYMBOL_REQUEST_SOURCE,
+};
 use crate::error::ContractError;
-use crate::msg::OracleResponsePacket;
-use crate::obi::PriceDataOutput;
-use crate::state::{PriceData, CONFIG, JOBS, PRICES};
+use crate::history::{record_request_outcome, record_symbol_outcome};
+use crate::msg::{
+    OracleRequestPacket, OracleResponsePacket, PriceUpdate, RequesterExecuteMsg,
+    SubscriberExecuteMsg,
+};
+use crate::obi::{ObiSchema, PriceDataOutput};
+use crate::payment::settle_payment;
+use crate::randomness::{execute_randomness_update, fail_randomness_request};
+use crate::reward::reward_keeper;
+use crate::state::{
+    BandchainRequest, BatchRequest, Job, JobResult, JobStatus, OnDemandRequest, PriceData,
+    RandomnessStatus, RawResult, RequestOutcome, SymbolRequest, AGGREGATIONS, BANDCHAIN_REQUESTS,
+    BATCH_REQUESTS, CONFIG, JOBS, JOB_RESULTS, JOB_STATUS, ON_DEMAND_REQUESTS, PENDING_REQUESTS,
+    PRICES, PRICE_OVERRIDES, RANDOMNESS, RAW_RESULTS, SOURCE_PRICES, SUBSCRIPTIONS,
+    SYMBOL_REQUESTS,
+};
 
 pub const IBC_VERSION: &str = "bandchain-1";
 
+pub const SUBSCRIBER_CALLBACK_REPLY_ID: u64 = 1;
+pub const REQUESTER_CALLBACK_REPLY_ID: u64 = 2;
+const CALLBACK_GAS_LIMIT: u64 = 500000;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn ibc_channel_open(
     _deps: DepsMut,
@@ -60,16 +84,53 @@ pub fn ibc_packet_receive(
 ) -> Result<IbcReceiveResponse, ContractError> {
     match try_ibc_packet_receive(deps, env, msg) {
         Ok(response) => Ok(response),
-        Err(error) => fail_packet_receive(&error.to_string()),
+        Err(error) => fail_packet_receive(AckErrorCode::from(&error), &error.to_string()),
     }
 }
 
 pub fn try_ibc_packet_receive(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     msg: IbcPacketReceiveMsg,
 ) -> Result<IbcReceiveResponse, ContractError> {
     let channel = msg.packet.dest.channel_id;
+    let packet: OracleResponsePacket = from_binary(&msg.packet.data)?;
+    let bandchain_request_id = u64::from_str(&packet.request_id).ok();
+    let response = execute_update(deps.branch(), env, channel, msg.packet.sequence, packet);
+
+    // The response stays indexed only if some stored price now comes from it
+    if let Some(bandchain_request_id) = bandchain_request_id {
+        prune_bandchain_request(deps.storage, bandchain_request_id)?;
+    }
+    response
+}
+
+pub fn execute_update(
+    deps: DepsMut,
+    env: Env,
+    channel: String,
+    sequence: u64,
+    packet: OracleResponsePacket,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if channel != config.channel {
+        return fail_packet_receive(
+            AckErrorCode::WrongChannel,
+            "Received packet coming from the wrong channel",
+        );
+    }
+    record_bandchain_request(deps.storage, channel, sequence, &packet)?;
+
+    if let Some(batch) = BATCH_REQUESTS.may_load(deps.storage, &packet.client_id)? {
+        BATCH_REQUESTS.remove(deps.storage, &packet.client_id);
+        return execute_batch_update(deps.storage, &env, batch, packet);
+    }
+
+    if let Some(request) = SYMBOL_REQUESTS.may_load(deps.storage, &packet.client_id)? {
+        SYMBOL_REQUESTS.remove(deps.storage, &packet.client_id);
+        return execute_symbol_update(deps.storage, &env, request, packet);
+    }
+
     let OracleResponsePacket {
         client_id,
         request_id,
@@ -77,87 +138,1303 @@ pub fn try_ibc_packet_receive(
         resolve_status,
         result,
         ..
-    } = from_binary(&msg.packet.data)?;
-    execute_update(
-        deps,
-        channel,
+    } = packet;
+
+    if let Some(request) = ON_DEMAND_REQUESTS.may_load(deps.storage, &client_id)? {
+        ON_DEMAND_REQUESTS.remove(deps.storage, &client_id);
+        return execute_on_demand_update(
+            deps.storage,
+            &env,
+            client_id,
+            request,
+            request_id,
+            resolve_time,
+            resolve_status,
+            result,
+        );
+    }
+
+    if let Some(request) = RANDOMNESS.may_load(deps.storage, &client_id)? {
+        if request.status != RandomnessStatus::Pending {
+            return fail_packet_receive(
+                AckErrorCode::Stale,
+                "Response to a request that is no longer pending",
+            );
+        }
+        return execute_randomness_update(
+            deps.storage,
+            client_id,
+            request,
+            request_id,
+            resolve_time,
+            resolve_status,
+            result,
+        );
+    }
+
+    let job_id = client_job_id(&client_id).to_string();
+    let job = match JOBS.may_load(deps.storage, &job_id) {
+        Ok(Some(data)) => data,
+        Ok(None) => return fail_packet_receive(AckErrorCode::UnknownClientId, "Invalid client id"),
+        Err(e) => return fail_packet_receive(AckErrorCode::Internal, &e.to_string()),
+    };
+
+    // A response to a request that has since been replaced or settled is dropped
+    match PENDING_REQUESTS.may_load(deps.storage, &job_id)? {
+        Some(pending) if pending.client_id == client_id => {}
+        _ => {
+            return fail_packet_receive(
+                AckErrorCode::Stale,
+                "Response to a request that is no longer pending",
+            )
+        }
+    }
+
+    let fail = |storage: &mut dyn Storage,
+                outcome: RequestOutcome,
+                code: AckErrorCode,
+                error: &str|
+     -> Result<IbcReceiveResponse, ContractError> {
+        let refunds = settle_job_request(
+            storage,
+            &env,
+            &job_id,
+            &client_id,
+            outcome,
+            u64::from_str(&request_id).ok(),
+            u64::from_str(&resolve_time).ok(),
+        )?;
+        Ok(fail_packet_receive(code, error)?.add_messages(refunds))
+    };
+
+    if resolve_status != *"RESOLVE_STATUS_SUCCESS" {
+        return fail(
+            deps.storage,
+            unresolved_outcome(&resolve_status),
+            AckErrorCode::UnresolvedStatus,
+            "Band request did not resolve successfully",
+        );
+    }
+
+    let depth = PENDING_REQUESTS
+        .may_load(deps.storage, &job_id)?
+        .map(|pending| pending.depth)
+        .unwrap_or_default();
+
+    // Generic jobs keep their decoded result instead of writing prices
+    let stored = match &job.schema {
+        Some(schema) => {
+            let decoded = parse_number("request_id", &request_id).and_then(|request_id| {
+                let resolve_time = parse_number("resolve_time", &resolve_time)?;
+                let value = ObiSchema::parse(schema)?.decode_output(&result)?;
+                Ok((request_id, resolve_time, value))
+            });
+            let (bandchain_request_id, bandchain_resolve_time, value) = match decoded {
+                Ok(decoded) => decoded,
+                Err(error) => {
+                    return fail(
+                        deps.storage,
+                        RequestOutcome::Failure,
+                        AckErrorCode::from(&error),
+                        &error.to_string(),
+                    )
+                }
+            };
+            store_raw_result(
+                deps.storage,
+                &job_id,
+                bandchain_request_id,
+                &RawResult {
+                    symbols: vec![],
+                    result,
+                    resolve_time: bandchain_resolve_time,
+                },
+            )?;
+            record_job_success(
+                deps.storage,
+                &env,
+                &job_id,
+                &client_id,
+                &job,
+                bandchain_request_id,
+                bandchain_resolve_time,
+            )?;
+            JOB_RESULTS.save(
+                deps.storage,
+                &job_id,
+                &JobResult {
+                    result: value.to_string(),
+                    bandchain_request_id,
+                    bandchain_resolve_time,
+                },
+            )?;
+            StoredPrices::default()
+        }
+        None => {
+            let (rates, bandchain_request_id, bandchain_resolve_time) =
+                match decode_job_rates(&job, &request_id, &resolve_time, &result) {
+                    Ok(decoded) => decoded,
+                    Err(error) => {
+                        return fail(
+                            deps.storage,
+                            RequestOutcome::Failure,
+                            AckErrorCode::from(&error),
+                            &error.to_string(),
+                        )
+                    }
+                };
+            if job.symbols.len() != rates.len() {
+                return fail(
+                    deps.storage,
+                    RequestOutcome::Failure,
+                    AckErrorCode::LengthMismatch,
+                    "Result and Calldata length mismatched",
+                );
+            }
+            if let Err(error) = scale_job_rates(&job_id, &job, &rates) {
+                return fail(
+                    deps.storage,
+                    RequestOutcome::Failure,
+                    AckErrorCode::from(&error),
+                    &error.to_string(),
+                );
+            }
+            store_raw_result(
+                deps.storage,
+                &job_id,
+                bandchain_request_id,
+                &RawResult {
+                    symbols: job.symbols.clone(),
+                    result,
+                    resolve_time: bandchain_resolve_time,
+                },
+            )?;
+            apply_job_rates(
+                deps.storage,
+                &env,
+                &job_id,
+                &client_id,
+                &job,
+                &rates,
+                bandchain_request_id,
+                bandchain_resolve_time,
+            )?
+        }
+    };
+    let (follow_ups, follow_up_ids) = follow_up_requests(deps.storage, &env, &job, depth)?;
+
+    Ok(IbcReceiveResponse::new()
+        .add_submessages(subscriber_callbacks(
+            deps.storage,
+            env.block.time.seconds(),
+            stored.published,
+        )?)
+        .add_messages(follow_ups)
+        .add_attributes(vec![
+            attr("method", "execute_update"),
+            attr("job_id", job_id),
+            attr("client_id", client_id),
+            attr("updated_symbols", stored.updated_symbols.join(",")),
+            attr("failed_symbols", stored.failed_symbols.join(",")),
+            attr("insufficient_sources", stored.pending_symbols.join(",")),
+            attr("follow_ups", follow_up_ids.join(",")),
+        ])
+        .set_ack(make_ack_success()))
+}
+
+/// ## Description
+/// Decodes the rates of a successful oracle response along with its BandChain request ID and resolve time.
+///
+/// ## Params
+/// - **request_id** is the BandChain request ID associated with the response.
+///
+/// - **resolve_time** is the time the request was resolved on BandChain.
+///
+/// - **result** is the OBI-encoded result of the request.
+pub fn decode_price_result(
+    request_id: &str,
+    resolve_time: &str,
+    result: &str,
+) -> Result<(Vec<u64>, u64, u64), ContractError> {
+    let bandchain_request_id = parse_number("request_id", request_id)?;
+    let bandchain_resolve_time = parse_number("resolve_time", resolve_time)?;
+    let PriceDataOutput { rates } = PriceDataOutput::decode_obi(result)?;
+
+    Ok((rates, bandchain_request_id, bandchain_resolve_time))
+}
+
+/// ## Description
+/// Decodes the rates of a successful oracle response for a job according to its calldata variant,
+/// along with its BandChain request ID and resolve time. Unavailable symbols have no rate.
+///
+/// ## Params
+/// - **job** is an object of type [`Job`] which is the job the rates were requested for.
+///
+/// - **request_id** is the BandChain request ID associated with the response.
+///
+/// - **resolve_time** is the time the request was resolved on BandChain.
+///
+/// - **result** is the OBI-encoded result of the request.
+pub fn decode_job_rates(
+    job: &Job,
+    request_id: &str,
+    resolve_time: &str,
+    result: &str,
+) -> Result<(Vec<Option<u64>>, u64, u64), ContractError> {
+    let bandchain_request_id = parse_number("request_id", request_id)?;
+    let bandchain_resolve_time = parse_number("resolve_time", resolve_time)?;
+    let rates = job.calldata_variant.decode_rates(&job.symbols, result)?;
+
+    Ok((rates, bandchain_request_id, bandchain_resolve_time))
+}
+
+/// ## Description
+/// Parses a numeric field of an oracle response.
+///
+/// ## Params
+/// - **field** is the name of the field being parsed.
+///
+/// - **value** is the value of the field.
+pub fn parse_number(field: &str, value: &str) -> Result<u64, ContractError> {
+    u64::from_str(value).map_err(|_| ContractError::InvalidNumber {
+        field: field.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// ## Description
+/// Indexes the received response by its BandChain request ID, so that stored prices can be
+/// traced back to the request and packet that produced them. Only responses to pending job,
+/// batch and symbol requests are indexed, as no other response produces stored prices.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **channel** is the channel the response was received on.
+///
+/// - **sequence** is the sequence of the response packet on the channel.
+///
+/// - **packet** is an object of type [`OracleResponsePacket`] which is the response received from BandChain.
+pub fn record_bandchain_request(
+    storage: &mut dyn Storage,
+    channel: String,
+    sequence: u64,
+    packet: &OracleResponsePacket,
+) -> StdResult<()> {
+    let bandchain_request_id = match u64::from_str(&packet.request_id) {
+        Ok(bandchain_request_id) => bandchain_request_id,
+        Err(_) => return Ok(()),
+    };
+
+    let client_id = packet.client_id.as_str();
+    let (job_ids, symbols) = if let Some(batch) = BATCH_REQUESTS.may_load(storage, client_id)? {
+        (batch.job_ids, batch.symbols)
+    } else if let Some(request) = SYMBOL_REQUESTS.may_load(storage, client_id)? {
+        (vec![], request.symbols)
+    } else {
+        let job_id = client_job_id(client_id);
+        match (
+            PENDING_REQUESTS.may_load(storage, job_id)?,
+            JOBS.may_load(storage, job_id)?,
+        ) {
+            (Some(pending), Some(job)) if pending.client_id == client_id => {
+                (vec![job_id.to_string()], job.symbols)
+            }
+            _ => return Ok(()),
+        }
+    };
+
+    BANDCHAIN_REQUESTS.save(
+        storage,
+        bandchain_request_id,
+        &BandchainRequest {
+            client_id: client_id.to_string(),
+            job_ids,
+            symbols,
+            channel,
+            packet_sequence: sequence,
+            resolve_status: packet.resolve_status.clone(),
+            resolve_time: packet.resolve_time.clone(),
+            result: packet.result.clone(),
+        },
+    )
+}
+
+/// ## Description
+/// Drops an indexed response once no stored price of its symbols, aggregated or reported by a
+/// source, comes from it anymore.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **bandchain_request_id** is the BandChain request ID the response is indexed by.
+pub fn prune_bandchain_request(
+    storage: &mut dyn Storage,
+    bandchain_request_id: u64,
+) -> StdResult<()> {
+    let request = match BANDCHAIN_REQUESTS.may_load(storage, bandchain_request_id)? {
+        Some(request) => request,
+        None => return Ok(()),
+    };
+
+    for symbol in request.symbols.iter() {
+        let current = PRICES.may_load(storage, symbol)?.map_or(false, |price| {
+            price.bandchain_request_id == bandchain_request_id
+        });
+        if current {
+            return Ok(());
+        }
+        for item in SOURCE_PRICES
+            .prefix(symbol)
+            .range(storage, None, None, Order::Ascending)
+        {
+            let (_, price) = item?;
+            if price.bandchain_request_id == bandchain_request_id {
+                return Ok(());
+            }
+        }
+    }
+
+    BANDCHAIN_REQUESTS.remove(storage, bandchain_request_id);
+    Ok(())
+}
+
+/// ## Description
+/// Fans the result of a merged request back out to each of the jobs it was sent for.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **batch** is an object of type [`BatchRequest`] which is the merged request being resolved.
+///
+/// - **packet** is an object of type [`OracleResponsePacket`] which is the response received from BandChain.
+pub fn execute_batch_update(
+    storage: &mut dyn Storage,
+    env: &Env,
+    batch: BatchRequest,
+    packet: OracleResponsePacket,
+) -> Result<IbcReceiveResponse, ContractError> {
+    // Only the jobs still waiting on this request take part in it, the others were replaced since
+    let mut job_ids = vec![];
+    for job_id in batch.job_ids {
+        if let Some(pending) = PENDING_REQUESTS.may_load(storage, &job_id)? {
+            if pending.client_id == packet.client_id {
+                job_ids.push(job_id);
+            }
+        }
+    }
+    if job_ids.is_empty() {
+        return fail_packet_receive(
+            AckErrorCode::Stale,
+            "Response to a request that is no longer pending",
+        );
+    }
+
+    let fail = |storage: &mut dyn Storage,
+                code: AckErrorCode,
+                error: &str|
+     -> Result<IbcReceiveResponse, ContractError> {
+        let mut refunds = vec![];
+        for job_id in job_ids.iter() {
+            refunds.extend(settle_job_request(
+                storage,
+                env,
+                job_id,
+                &packet.client_id,
+                unresolved_outcome(&packet.resolve_status),
+                u64::from_str(&packet.request_id).ok(),
+                u64::from_str(&packet.resolve_time).ok(),
+            )?);
+        }
+        Ok(fail_packet_receive(code, error)?.add_messages(refunds))
+    };
+
+    if packet.resolve_status != *"RESOLVE_STATUS_SUCCESS" {
+        return fail(
+            storage,
+            AckErrorCode::UnresolvedStatus,
+            "Band request did not resolve successfully",
+        );
+    }
+
+    let (rates, bandchain_request_id, bandchain_resolve_time) =
+        match decode_price_result(&packet.request_id, &packet.resolve_time, &packet.result) {
+            Ok(decoded) => decoded,
+            Err(error) => return fail(storage, AckErrorCode::from(&error), &error.to_string()),
+        };
+    if batch.symbols.len() != rates.len() {
+        return fail(
+            storage,
+            AckErrorCode::LengthMismatch,
+            "Result and Calldata length mismatched",
+        );
+    }
+
+    let batch_rates: BTreeMap<&String, u64> = batch.symbols.iter().zip(rates).collect();
+    let mut stored = StoredPrices::default();
+    let mut follow_ups = vec![];
+    let mut follow_up_ids = vec![];
+    let mut jobs = vec![];
+    for job_id in job_ids.iter() {
+        let job = match JOBS.may_load(storage, job_id)? {
+            Some(job) => job,
+            None => continue,
+        };
+        // A job symbol missing from the batch is treated as unavailable instead of aborting the packet
+        let job_rates: Vec<Option<u64>> = job
+            .symbols
+            .iter()
+            .map(|symbol| batch_rates.get(symbol).copied())
+            .collect();
+        if let Err(error) = scale_job_rates(job_id, &job, &job_rates) {
+            return fail(storage, AckErrorCode::from(&error), &error.to_string());
+        }
+        jobs.push((job_id, job, job_rates));
+    }
+    for (job_id, job, job_rates) in jobs {
+        store_raw_result(
+            storage,
+            job_id,
+            bandchain_request_id,
+            &RawResult {
+                symbols: batch.symbols.clone(),
+                result: packet.result.clone(),
+                resolve_time: bandchain_resolve_time,
+            },
+        )?;
+        let depth = PENDING_REQUESTS
+            .may_load(storage, job_id)?
+            .map(|pending| pending.depth)
+            .unwrap_or_default();
+        let job_stored = apply_job_rates(
+            storage,
+            env,
+            job_id,
+            &packet.client_id,
+            &job,
+            &job_rates,
+            bandchain_request_id,
+            bandchain_resolve_time,
+        )?;
+        stored.extend(job_stored);
+
+        let (job_follow_ups, job_follow_up_ids) = follow_up_requests(storage, env, &job, depth)?;
+        follow_ups.extend(job_follow_ups);
+        follow_up_ids.extend(job_follow_up_ids);
+    }
+
+    Ok(IbcReceiveResponse::new()
+        .add_submessages(subscriber_callbacks(
+            storage,
+            env.block.time.seconds(),
+            stored.published,
+        )?)
+        .add_messages(follow_ups)
+        .add_attributes(vec![
+            attr("method", "execute_batch_update"),
+            attr("client_id", packet.client_id),
+            attr("job_ids", job_ids.join(",")),
+            attr("updated_symbols", stored.updated_symbols.join(",")),
+            attr("failed_symbols", stored.failed_symbols.join(",")),
+            attr("insufficient_sources", stored.pending_symbols.join(",")),
+            attr("follow_ups", follow_up_ids.join(",")),
+        ])
+        .set_ack(make_ack_success()))
+}
+
+/// ## Description
+/// Stores the result of a request for symbols that have no registered job, just like the rates
+/// of a registered job, under the [`SYMBOL_REQUEST_SOURCE`] source. Aggregated symbols only take
+/// the new rate into account if the source is one of their price sources. Symbols reported with
+/// a zero rate keep their previous price.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **request** is an object of type [`SymbolRequest`] which is the in-flight request being resolved.
+///
+/// - **packet** is an object of type [`OracleResponsePacket`] which is the response received from BandChain.
+pub fn execute_symbol_update(
+    storage: &mut dyn Storage,
+    env: &Env,
+    request: SymbolRequest,
+    packet: OracleResponsePacket,
+) -> Result<IbcReceiveResponse, ContractError> {
+    if packet.resolve_status != *"RESOLVE_STATUS_SUCCESS" {
+        return fail_packet_receive(
+            AckErrorCode::UnresolvedStatus,
+            "Band request did not resolve successfully",
+        );
+    }
+
+    let (rates, bandchain_request_id, bandchain_resolve_time) =
+        decode_price_result(&packet.request_id, &packet.resolve_time, &packet.result)?;
+    if request.symbols.len() != rates.len() {
+        return fail_packet_receive(
+            AckErrorCode::LengthMismatch,
+            "Result and Calldata length mismatched",
+        );
+    }
+
+    let now = env.block.time.seconds();
+    let rates: Vec<Option<Decimal>> = rates
+        .iter()
+        .map(|&rate| Some(Decimal::from_ratio(rate, request.multiplier)))
+        .collect();
+    let stored = store_source_prices(
+        storage,
+        now,
+        SYMBOL_REQUEST_SOURCE,
+        &request.symbols,
+        &rates,
+        bandchain_request_id,
+        bandchain_resolve_time,
+    )?;
+    record_symbol_outcomes(storage, &stored, now)?;
+
+    Ok(IbcReceiveResponse::new()
+        .add_submessages(subscriber_callbacks(storage, now, stored.published)?)
+        .add_attributes(vec![
+            attr("method", "execute_symbol_update"),
+            attr("client_id", packet.client_id),
+            attr("symbols", request.symbols.join(",")),
+            attr("updated_symbols", stored.updated_symbols.join(",")),
+            attr("failed_symbols", stored.failed_symbols.join(",")),
+            attr("insufficient_sources", stored.pending_symbols.join(",")),
+        ])
+        .set_ack(make_ack_success()))
+}
+
+/// ## Description
+/// Settles the request of a job that resolved successfully, stores the rates it received and
+/// updates the failure counters of its symbols.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **job_id** is the ID of the job the rates were requested for.
+///
+/// - **client_id** is the unique identifier the request was sent with.
+///
+/// - **job** is an object of type [`Job`] which is the job the rates were requested for.
+///
+/// - **rates** is the list of rates received for the job's symbols, in order. A missing rate
+///   leaves the symbol's previous price in place.
+///
+/// - **bandchain_request_id** is the BandChain request ID associated with the response.
+///
+/// - **bandchain_resolve_time** is the time the request was resolved on BandChain.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_job_rates(
+    storage: &mut dyn Storage,
+    env: &Env,
+    job_id: &str,
+    client_id: &str,
+    job: &Job,
+    rates: &[Option<u64>],
+    bandchain_request_id: u64,
+    bandchain_resolve_time: u64,
+) -> Result<StoredPrices, ContractError> {
+    record_job_success(
+        storage,
+        env,
+        job_id,
         client_id,
-        request_id,
+        job,
+        bandchain_request_id,
+        bandchain_resolve_time,
+    )?;
+
+    let now = env.block.time.seconds();
+    let stored = store_job_prices(
+        storage,
+        now,
+        job_id,
+        job,
+        rates,
+        bandchain_request_id,
+        bandchain_resolve_time,
+    )?;
+    record_symbol_outcomes(storage, &stored, now)?;
+
+    Ok(stored)
+}
+
+/// ## Description
+/// Settles the request of a job that resolved successfully and records the time of the success.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **job_id** is the ID of the job the request was sent for.
+///
+/// - **client_id** is the unique identifier the request was sent with.
+///
+/// - **job** is an object of type [`Job`] which is the job the request was sent for.
+///
+/// - **bandchain_request_id** is the BandChain request ID associated with the response.
+///
+/// - **bandchain_resolve_time** is the time the request was resolved on BandChain.
+pub fn record_job_success(
+    storage: &mut dyn Storage,
+    env: &Env,
+    job_id: &str,
+    client_id: &str,
+    job: &Job,
+    bandchain_request_id: u64,
+    bandchain_resolve_time: u64,
+) -> StdResult<()> {
+    settle_job_request(
+        storage,
+        env,
+        job_id,
+        client_id,
+        RequestOutcome::Success,
+        Some(bandchain_request_id),
+        Some(bandchain_resolve_time),
+    )?;
+    let previous_due_at = job_due_at(storage, job_id, job)?;
+    JOB_STATUS.update(storage, job_id, |status| -> StdResult<_> {
+        let mut status: JobStatus = status.unwrap_or_default();
+        status.last_success = Some(env.block.time.seconds());
+        Ok(status)
+    })?;
+    reindex_due_job(storage, job_id, job, previous_due_at)
+}
+
+/// ## Description
+/// Stores the rates received for a job as its source prices, aggregating them where configured.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **now** is the current block time in seconds.
+///
+/// - **job_id** is the ID of the job the rates were requested for.
+///
+/// - **job** is an object of type [`Job`] which is the job the rates were requested for.
+///
+/// - **rates** is the list of rates received for the job's symbols, in order. A missing rate
+///   leaves the symbol's previous price in place.
+///
+/// - **bandchain_request_id** is the BandChain request ID associated with the response.
+///
+/// - **bandchain_resolve_time** is the time the request was resolved on BandChain.
+pub fn store_job_prices(
+    storage: &mut dyn Storage,
+    now: u64,
+    job_id: &str,
+    job: &Job,
+    rates: &[Option<u64>],
+    bandchain_request_id: u64,
+    bandchain_resolve_time: u64,
+) -> Result<StoredPrices, ContractError> {
+    let rates = scale_job_rates(job_id, job, rates)?;
+    Ok(store_source_prices(
+        storage,
+        now,
+        job_id,
+        &job.symbols,
+        &rates,
+        bandchain_request_id,
+        bandchain_resolve_time,
+    )?)
+}
+
+/// ## Description
+/// Scales every rate received for a job by the multiplier of its symbol, so that a response can
+/// be rejected before any of its rates are applied.
+///
+/// ## Params
+/// - **job_id** is the ID of the job the rates were received for.
+///
+/// - **job** is an object of type [`Job`] which is the job the rates were received for.
+///
+/// - **rates** is the list of rates received for the job's symbols, if available.
+pub fn scale_job_rates(
+    job_id: &str,
+    job: &Job,
+    rates: &[Option<u64>],
+) -> Result<Vec<Option<Decimal>>, ContractError> {
+    rates
+        .iter()
+        .enumerate()
+        .map(|(index, rate)| {
+            let multiplier =
+                job.multiplier_of(index)
+                    .ok_or_else(|| ContractError::NotPriceJob {
+                        job_id: job_id.to_string(),
+                    })?;
+            rate.map(|rate| scale_rate(rate, multiplier)).transpose()
+        })
+        .collect()
+}
+
+/// ## Description
+/// This structure describes the outcome of storing the rates received from a price source.
+#[derive(Default)]
+pub struct StoredPrices {
+    /// The newly published prices
+    pub published: Vec<PriceUpdate>,
+    /// The symbols still lacking enough fresh sources to be aggregated
+    pub pending_symbols: Vec<String>,
+    /// The symbols a rate was stored for
+    pub updated_symbols: Vec<String>,
+    /// The symbols the response held no usable rate for
+    pub failed_symbols: Vec<String>,
+}
+
+impl StoredPrices {
+    fn extend(&mut self, other: StoredPrices) {
+        self.published.extend(other.published);
+        self.pending_symbols.extend(other.pending_symbols);
+        self.updated_symbols.extend(other.updated_symbols);
+        self.failed_symbols.extend(other.failed_symbols);
+    }
+}
+
+/// ## Description
+/// Stores the rates received from a price source, aggregating them where configured.
+/// Missing and zero rates, which some oracle scripts report for unavailable symbols, leave the
+/// previous prices untouched.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **now** is the current block time in seconds.
+///
+/// - **source** is the ID of the source the rates were received from.
+///
+/// - **symbols** is the list of symbols the rates were requested for.
+///
+/// - **rates** is the list of rates received for the symbols, in order, if available.
+///
+/// - **bandchain_request_id** is the BandChain request ID associated with the response.
+///
+/// - **bandchain_resolve_time** is the time the request was resolved on BandChain.
+pub fn store_source_prices(
+    storage: &mut dyn Storage,
+    now: u64,
+    source: &str,
+    symbols: &[String],
+    rates: &[Option<Decimal>],
+    bandchain_request_id: u64,
+    bandchain_resolve_time: u64,
+) -> StdResult<StoredPrices> {
+    let mut stored = StoredPrices::default();
+    let mut replaced = vec![];
+    for (symbol, rate) in symbols.iter().zip(rates.iter()) {
+        let rate = match rate {
+            Some(rate) if !rate.is_zero() => *rate,
+            _ => {
+                stored.failed_symbols.push(symbol.clone());
+                continue;
+            }
+        };
+        stored.updated_symbols.push(symbol.clone());
+        let price = PriceData {
+            rate,
+            bandchain_request_id,
+            bandchain_resolve_time,
+        };
+        if let Some(previous) = SOURCE_PRICES.may_load(storage, (symbol, source))? {
+            replaced.push(previous.bandchain_request_id);
+        }
+        if let Some(previous) = PRICES.may_load(storage, symbol)? {
+            replaced.push(previous.bandchain_request_id);
+        }
+        SOURCE_PRICES.save(storage, (symbol, source), &price)?;
+
+        match AGGREGATIONS.may_load(storage, symbol)? {
+            Some(aggregation) => match aggregate_price(storage, symbol, &aggregation, now)? {
+                Some(aggregated) => {
+                    PRICES.save(storage, symbol, &aggregated)?;
+                    stored.published.push(PriceUpdate {
+                        symbol: symbol.clone(),
+                        price: aggregated,
+                    });
+                }
+                None => stored.pending_symbols.push(symbol.clone()),
+            },
+            None => {
+                PRICES.save(storage, symbol, &price)?;
+                stored.published.push(PriceUpdate {
+                    symbol: symbol.clone(),
+                    price,
+                });
+            }
+        }
+    }
+
+    for replaced_request_id in replaced {
+        if replaced_request_id != bandchain_request_id {
+            prune_bandchain_request(storage, replaced_request_id)?;
+        }
+    }
+
+    Ok(stored)
+}
+
+/// ## Description
+/// Updates the failure counters of the symbols received in a successful response.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **stored** is an object of type [`StoredPrices`] which is the outcome of storing the response.
+///
+/// - **now** is the current block time in seconds.
+fn record_symbol_outcomes(
+    storage: &mut dyn Storage,
+    stored: &StoredPrices,
+    now: u64,
+) -> StdResult<()> {
+    for symbol in stored.updated_symbols.iter() {
+        record_symbol_outcome(storage, symbol, true, now)?;
+    }
+    for symbol in stored.failed_symbols.iter() {
+        record_symbol_outcome(storage, symbol, false, now)?;
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Keeps the raw result of a successful request of a job, dropping the oldest results of the job
+/// beyond the configured retention.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **job_id** is the ID of the job the request was sent for.
+///
+/// - **bandchain_request_id** is the BandChain request ID associated with the response.
+///
+/// - **result** is an object of type [`RawResult`] which is the raw result to keep.
+pub fn store_raw_result(
+    storage: &mut dyn Storage,
+    job_id: &str,
+    bandchain_request_id: u64,
+    result: &RawResult,
+) -> StdResult<()> {
+    let retention = CONFIG
+        .load(storage)?
+        .result_retention
+        .unwrap_or(DEFAULT_RESULT_RETENTION) as usize;
+    if retention > 0 {
+        RAW_RESULTS.save(storage, (job_id, bandchain_request_id), result)?;
+    }
+
+    let request_ids = RAW_RESULTS
+        .prefix(job_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
+    for request_id in request_ids
+        .iter()
+        .take(request_ids.len().saturating_sub(retention))
+    {
+        RAW_RESULTS.remove(storage, (job_id, *request_id));
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns how a job request that did not resolve into a usable result ended.
+///
+/// ## Params
+/// - **resolve_status** is the status of the request on BandChain.
+fn unresolved_outcome(resolve_status: &str) -> RequestOutcome {
+    match resolve_status {
+        "RESOLVE_STATUS_EXPIRED" => RequestOutcome::Expired,
+        _ => RequestOutcome::Failure,
+    }
+}
+
+/// ## Description
+/// Clears the in-flight request of a job once it resolved. If the request succeeded, the escrowed
+/// payment is collected into the treasury and the requester is credited the keeper reward if the
+/// job was due when the request was sent.
+/// Otherwise the escrowed payment is refunded to the requester.
+/// Nothing is settled if the pending request was sent with another client ID, as the packet
+/// then belongs to a request that was already replaced.
+///
+/// The outcome is recorded in the job's request history.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **job_id** is the ID of the job the request was sent for.
+///
+/// - **client_id** is the unique identifier the request was sent with.
+///
+/// - **outcome** is an object of type [`RequestOutcome`] which is how the request ended.
+///
+/// - **bandchain_request_id** is an [`Option`] of type [`u64`] which is the BandChain request ID, if known.
+///
+/// - **resolve_time** is an [`Option`] of type [`u64`] which is the time the request was resolved on BandChain, if it was.
+pub fn settle_job_request(
+    storage: &mut dyn Storage,
+    env: &Env,
+    job_id: &str,
+    client_id: &str,
+    outcome: RequestOutcome,
+    bandchain_request_id: Option<u64>,
+    resolve_time: Option<u64>,
+) -> StdResult<Vec<CosmosMsg>> {
+    let pending = match PENDING_REQUESTS.may_load(storage, job_id)? {
+        Some(pending) if pending.client_id == client_id => pending,
+        _ => return Ok(vec![]),
+    };
+    PENDING_REQUESTS.remove(storage, job_id);
+
+    let succeeded = outcome == RequestOutcome::Success;
+    record_request_outcome(
+        storage,
+        job_id,
+        &pending,
+        outcome,
+        bandchain_request_id,
         resolve_time,
-        resolve_status,
-        result,
-    )
+        env.block.time.seconds(),
+    )?;
+
+    if succeeded && pending.rewarded {
+        reward_keeper(storage, &pending.requester)?;
+    }
+    settle_payment(storage, pending.payment, &pending.requester, succeeded)
 }
 
-pub fn execute_update(
-    deps: DepsMut,
-    channel: String,
+/// ## Description
+/// Delivers the result of a one-off price request back to the contract that requested it.
+/// Symbols reported with a zero rate are unavailable, so they are left out of the delivered prices
+/// and counted as failures. A failure callback is sent instead if the request did not resolve
+/// into a usable result.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **client_id** is the unique identifier of the oracle request.
+///
+/// - **request** is an object of type [`OnDemandRequest`] which is the in-flight request being resolved.
+///
+/// - **request_id** is the BandChain request ID associated with the response.
+///
+/// - **resolve_time** is the time the request was resolved on BandChain.
+///
+/// - **resolve_status** is the status of the request on BandChain.
+///
+/// - **result** is the OBI-encoded result of the request.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_on_demand_update(
+    storage: &mut dyn Storage,
+    env: &Env,
     client_id: String,
+    request: OnDemandRequest,
     request_id: String,
     resolve_time: String,
     resolve_status: String,
     result: String,
 ) -> Result<IbcReceiveResponse, ContractError> {
+    let fail = |storage: &mut dyn Storage,
+                code: AckErrorCode,
+                error: &str|
+     -> Result<IbcReceiveResponse, ContractError> {
+        let refunds = settle_payment(storage, request.payment.clone(), &request.requester, false)?;
+        Ok(fail_packet_receive(code, error)?
+            .add_messages(refunds)
+            .add_submessage(requester_callback(
+                &request.requester,
+                &RequesterExecuteMsg::ReceivePriceFailure {
+                    client_id: client_id.clone(),
+                    error: error.to_string(),
+                },
+            )?))
+    };
+
     if resolve_status != *"RESOLVE_STATUS_SUCCESS" {
-        return fail_packet_receive("Band request did not resolve successfully");
+        return fail(
+            storage,
+            AckErrorCode::UnresolvedStatus,
+            "Band request did not resolve successfully",
+        );
     }
 
-    let config = CONFIG.load(deps.storage)?;
-    if channel != config.channel {
-        return fail_packet_receive("Received packet coming from the wrong channel");
+    let (rates, bandchain_request_id, bandchain_resolve_time) =
+        match decode_price_result(&request_id, &resolve_time, &result) {
+            Ok(decoded) => decoded,
+            Err(error) => return fail(storage, AckErrorCode::from(&error), &error.to_string()),
+        };
+    if request.symbols.len() != rates.len() {
+        return fail(
+            storage,
+            AckErrorCode::LengthMismatch,
+            "Result and Calldata length mismatched",
+        );
     }
 
-    let PriceDataOutput { rates } = PriceDataOutput::decode_obi(result.as_str())?;
-    let job = match JOBS.may_load(deps.storage, &client_id) {
-        Ok(Some(data)) => data,
-        Ok(None) => return fail_packet_receive("Invalid client id"),
-        Err(e) => return fail_packet_receive(&e.to_string()),
-    };
-
-    if job.symbols.len() != rates.len() {
-        return fail_packet_receive("Result and Calldata length mismatched");
-    }
-    job.symbols
-        .iter()
-        .zip(rates.iter())
-        .for_each(|(symbol, &rate)| {
-            PRICES
-                .save(
-                    deps.storage,
-                    symbol,
-                    &PriceData {
-                        rate: Decimal::from_ratio(rate, job.multiplier),
-                        bandchain_request_id: u64::from_str(request_id.as_str()).unwrap(),
-                        bandchain_resolve_time: u64::from_str(resolve_time.as_str()).unwrap(),
-                    },
-                )
-                .unwrap()
+    let mut prices = vec![];
+    let mut failed_symbols = vec![];
+    for (symbol, &rate) in request.symbols.iter().zip(rates.iter()) {
+        let rate = match scale_rate(rate, request.multiplier) {
+            Ok(rate) => rate,
+            Err(error) => return fail(storage, AckErrorCode::from(&error), &error.to_string()),
+        };
+        if rate.is_zero() {
+            failed_symbols.push(symbol.clone());
+            continue;
+        }
+        prices.push(PriceUpdate {
+            symbol: symbol.clone(),
+            price: PriceData {
+                rate,
+                bandchain_request_id,
+                bandchain_resolve_time,
+            },
         });
+    }
+    let now = env.block.time.seconds();
+    for symbol in request.symbols.iter() {
+        record_symbol_outcome(storage, symbol, !failed_symbols.contains(symbol), now)?;
+    }
+    settle_payment(storage, request.payment, &request.requester, true)?;
 
     Ok(IbcReceiveResponse::new()
+        .add_submessage(requester_callback(
+            &request.requester,
+            &RequesterExecuteMsg::ReceivePriceResponse {
+                client_id: client_id.clone(),
+                prices,
+            },
+        )?)
         .add_attributes(vec![
-            attr("method", "execute_update"),
-            attr("job_id", client_id),
+            attr("method", "execute_on_demand_update"),
+            attr("client_id", client_id),
+            attr("requester", request.requester),
+            attr("failed_symbols", failed_symbols.join(",")),
         ])
         .set_ack(make_ack_success()))
 }
 
+/// ## Description
+/// Scales a raw oracle rate down by the multiplier it was requested with.
+///
+/// ## Params
+/// - **rate** is the rate reported by the oracle.
+///
+/// - **multiplier** is the multiplier the rate was requested with.
+pub fn scale_rate(rate: u64, multiplier: u64) -> Result<Decimal, ContractError> {
+    Decimal::checked_from_ratio(rate, multiplier)
+        .map_err(|_| ContractError::InvalidRate { rate, multiplier })
+}
+
+/// ## Description
+/// Builds the callback delivering the outcome of a one-off price request to its requester,
+/// sent as a submessage replying on error so that a failing requester never fails the packet.
+///
+/// ## Params
+/// - **requester** is an object of type [`Addr`] which is the address of the requesting contract.
+///
+/// - **msg** is an object of type [`RequesterExecuteMsg`] which is the callback to send.
+pub fn requester_callback(requester: &Addr, msg: &RequesterExecuteMsg) -> StdResult<SubMsg> {
+    Ok(SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: requester.to_string(),
+            msg: to_binary(msg)?,
+            funds: vec![],
+        },
+        REQUESTER_CALLBACK_REPLY_ID,
+    )
+    .with_gas_limit(CALLBACK_GAS_LIMIT))
+}
+
+/// ## Description
+/// Builds the callbacks notifying subscribed contracts of newly published prices.
+/// Each subscriber receives a single callback with all of its updated symbols, sent as a
+/// submessage replying on error so that a failing subscriber never fails the packet.
+/// Only prices that the Price query serves are pushed: symbols with an active price override
+/// are skipped, and so are prices from a job other than the symbol's selected price source.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **now** is the current block time in seconds.
+///
+/// - **published** is the list of newly published prices.
+pub fn subscriber_callbacks(
+    storage: &dyn Storage,
+    now: u64,
+    published: Vec<PriceUpdate>,
+) -> StdResult<Vec<SubMsg>> {
+    let mut updates: BTreeMap<Addr, Vec<PriceUpdate>> = BTreeMap::new();
+    for update in published {
+        if let Some(price_override) = PRICE_OVERRIDES.may_load(storage, &update.symbol)? {
+            if price_override.expires_at > now {
+                continue;
+            }
+        }
+        match load_oracle_price(storage, now, &update.symbol) {
+            Ok(served) if served.bandchain_request_id == update.price.bandchain_request_id => {}
+            _ => continue,
+        }
+        for subscriber in
+            SUBSCRIPTIONS
+                .prefix(&update.symbol)
+                .keys(storage, None, None, Order::Ascending)
+        {
+            updates.entry(subscriber?).or_default().push(update.clone());
+        }
+    }
+
+    updates
+        .into_iter()
+        .map(|(subscriber, prices)| {
+            Ok(SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: subscriber.to_string(),
+                    msg: to_binary(&SubscriberExecuteMsg::ReceivePriceUpdate { prices })?,
+                    funds: vec![],
+                },
+                SUBSCRIBER_CALLBACK_REPLY_ID,
+            )
+            .with_gas_limit(CALLBACK_GAS_LIMIT))
+        })
+        .collect()
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn ibc_packet_ack(
-    _deps: DepsMut,
-    _env: Env,
-    _ack: IbcPacketAckMsg,
+    deps: DepsMut,
+    env: Env,
+    ack: IbcPacketAckMsg,
 ) -> Result<IbcBasicResponse, ContractError> {
-    Ok(IbcBasicResponse::new().add_attribute("method", "ibc_packet_ack"))
+    let mut response = IbcBasicResponse::new().add_attribute("method", "ibc_packet_ack");
+
+    // A request rejected by BandChain will never resolve, so it is aborted right away
+    if let Ok(Ack::Error(error)) = from_binary(&ack.acknowledgement.data) {
+        let packet: OracleRequestPacket = from_binary(&ack.original_packet.data)?;
+        response = response.add_submessages(abort_request(
+            deps,
+            env,
+            packet.client_id,
+            RequestOutcome::Rejected,
+            error,
+        )?);
+    }
+
+    Ok(response)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn ibc_packet_timeout(
-    _deps: DepsMut,
-    _env: Env,
-    _msg: IbcPacketTimeoutMsg,
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
 ) -> Result<IbcBasicResponse, ContractError> {
-    Ok(IbcBasicResponse::new().add_attribute("method", "ibc_packet_timeout"))
+    let packet: OracleRequestPacket = from_binary(&msg.packet.data)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("method", "ibc_packet_timeout")
+        .add_submessages(abort_request(
+            deps,
+            env,
+            packet.client_id,
+            RequestOutcome::Timeout,
+            "Oracle request timed out".to_string(),
+        )?))
+}
+
+/// ## Description
+/// Clears an in-flight request that will never resolve. One-off and randomness requesters receive
+/// a failure callback, and any escrowed payment is refunded to the requester.
+///
+/// ## Params
+/// - **deps** is an object of type [`DepsMut`].
+///
+/// - **env** is an object of type [`Env`].
+///
+/// - **client_id** is the unique identifier of the oracle request.
+///
+/// - **outcome** is an object of type [`RequestOutcome`] which is how the request ended.
+///
+/// - **error** is the reason the request failed.
+pub fn abort_request(
+    deps: DepsMut,
+    env: Env,
+    client_id: String,
+    outcome: RequestOutcome,
+    error: String,
+) -> StdResult<Vec<SubMsg>> {
+    if let Some(request) = ON_DEMAND_REQUESTS.may_load(deps.storage, &client_id)? {
+        ON_DEMAND_REQUESTS.remove(deps.storage, &client_id);
+        let mut messages: Vec<SubMsg> =
+            settle_payment(deps.storage, request.payment, &request.requester, false)?
+                .into_iter()
+                .map(SubMsg::new)
+                .collect();
+        messages.push(requester_callback(
+            &request.requester,
+            &RequesterExecuteMsg::ReceivePriceFailure { client_id, error },
+        )?);
+        return Ok(messages);
+    }
+
+    if let Some(request) = RANDOMNESS.may_load(deps.storage, &client_id)? {
+        if request.status == RandomnessStatus::Pending {
+            return Ok(vec![fail_randomness_request(
+                deps.storage,
+                client_id,
+                request,
+                error,
+            )?]);
+        }
+    }
+
+    if SYMBOL_REQUESTS.has(deps.storage, &client_id) {
+        SYMBOL_REQUESTS.remove(deps.storage, &client_id);
+        return Ok(vec![]);
+    }
+
+    if let Some(batch) = BATCH_REQUESTS.may_load(deps.storage, &client_id)? {
+        BATCH_REQUESTS.remove(deps.storage, &client_id);
+        let mut messages = vec![];
+        for job_id in batch.job_ids.iter() {
+            messages.extend(settle_job_request(
+                deps.storage,
+                &env,
+                job_id,
+                &client_id,
+                outcome.clone(),
+                None,
+                None,
+            )?);
+        }
+        return Ok(messages.into_iter().map(SubMsg::new).collect());
+    }
+
+    Ok(settle_job_request(
+        deps.storage,
+        &env,
+        client_job_id(&client_id),
+        &client_id,
+        outcome,
+        None,
+        None,
+    )?
+    .into_iter()
+    .map(SubMsg::new)
+    .collect())
 }
 
 pub fn validate_order_and_version(
@@ -187,11 +1464,128 @@ pub fn validate_order_and_version(
     Ok(())
 }
 
-pub fn fail_packet_receive(err: &str) -> Result<IbcReceiveResponse, ContractError> {
+pub fn fail_packet_receive(
+    code: AckErrorCode,
+    err: &str,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let ack_error = AckError {
+        code,
+        message: err.to_string(),
+    };
     Ok(IbcReceiveResponse::new()
         .add_attribute("method", "ibc_packet_receive")
+        .add_attribute("error_code", code.as_str())
         .add_attribute("error", err.to_string())
-        .set_ack(make_ack_fail(err.to_string())))
+        .set_ack(make_ack_fail(
+            String::from_utf8(to_vec(&ack_error)?).map_err(StdError::from)?,
+        )))
+}
+
+/// ## Description
+/// This structure describes the failure acknowledgement of an oracle response, sent JSON-encoded
+/// as the error of an [`Ack`] so that relayers and BandChain can tell rejection reasons apart.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AckError {
+    /// The stable code of the rejection reason
+    pub code: AckErrorCode,
+    /// The human-readable description of the rejection
+    pub message: String,
+}
+
+/// ## Description
+/// This enum describes the stable codes of failure acknowledgements, also emitted as the
+/// `error_code` event attribute.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AckErrorCode {
+    /// The response came from a channel other than the configured one
+    WrongChannel,
+    /// The client ID does not match any known job
+    UnknownClientId,
+    /// The response belongs to a request that was replaced or settled, or carries an older result
+    Stale,
+    /// The request did not resolve successfully on BandChain
+    UnresolvedStatus,
+    /// The response or its result could not be decoded
+    InvalidResult,
+    /// The result holds a different number of rates than the symbols requested
+    LengthMismatch,
+    /// A rate falls outside the bounds the contract can represent
+    BoundsViolation,
+    /// The response could not be processed for another reason
+    Internal,
+}
+
+impl AckErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AckErrorCode::WrongChannel => "wrong_channel",
+            AckErrorCode::UnknownClientId => "unknown_client_id",
+            AckErrorCode::Stale => "stale",
+            AckErrorCode::UnresolvedStatus => "unresolved_status",
+            AckErrorCode::InvalidResult => "invalid_result",
+            AckErrorCode::LengthMismatch => "length_mismatch",
+            AckErrorCode::BoundsViolation => "bounds_violation",
+            AckErrorCode::Internal => "internal",
+        }
+    }
+}
+
+impl From<&ContractError> for AckErrorCode {
+    fn from(error: &ContractError) -> Self {
+        match error {
+            ContractError::InvalidBase64 { .. }
+            | ContractError::ObiDecode { .. }
+            | ContractError::TrailingBytes { .. }
+            | ContractError::InvalidNumber { .. }
+            | ContractError::Std(StdError::ParseErr { .. })
+            | ContractError::Std(StdError::InvalidBase64 { .. })
+            | ContractError::Std(StdError::InvalidUtf8 { .. }) => AckErrorCode::InvalidResult,
+            ContractError::InvalidRate { .. } => AckErrorCode::BoundsViolation,
+            ContractError::StaleResult { .. } | ContractError::RequestInFlight { .. } => {
+                AckErrorCode::Stale
+            }
+            ContractError::JobNotFound {} => AckErrorCode::UnknownClientId,
+            ContractError::Std(_)
+            | ContractError::IOError(_)
+            | ContractError::Unsupported {}
+            | ContractError::Unauthorized {}
+            | ContractError::ChannelNotSet {}
+            | ContractError::InvalidAggregation {}
+            | ContractError::SymbolNotCovered { .. }
+            | ContractError::InvalidOverrideExpiry {}
+            | ContractError::OverrideTooLong { .. }
+            | ContractError::SubscriberNotContract {}
+            | ContractError::TooManySubscribers { .. }
+            | ContractError::EmptySymbols {}
+            | ContractError::ZeroMultiplier {}
+            | ContractError::InvalidMultipliers {}
+            | ContractError::SymbolNotAllowed { .. }
+            | ContractError::DefaultRequestNotSet {}
+            | ContractError::ResultNotFound { .. }
+            | ContractError::SymbolRequestedRecently { .. }
+            | ContractError::FollowUpCycle { .. }
+            | ContractError::InvalidFunds { .. }
+            | ContractError::UnsupportedToken {}
+            | ContractError::NoFunds {}
+            | ContractError::NoRewards {}
+            | ContractError::InsufficientFees {}
+            | ContractError::NonPayable {}
+            | ContractError::OrderedChannel {}
+            | ContractError::InvalidVersion { .. }
+            | ContractError::InvalidJson { .. }
+            | ContractError::InvalidObiSchema { .. }
+            | ContractError::ObiEncode { .. }
+            | ContractError::InvalidCalldata { .. }
+            | ContractError::WrongOracleScriptKind { .. }
+            | ContractError::MissingRequestParams { .. }
+            | ContractError::InvalidRequestParams {}
+            | ContractError::MissingSchema { .. }
+            | ContractError::NotPriceJob { .. }
+            | ContractError::VrfRequestNotSet {}
+            | ContractError::EmptySeed {} => AckErrorCode::Internal,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -210,3 +1604,1031 @@ pub fn make_ack_fail(err: String) -> Binary {
     let res = Ack::Error(err);
     to_binary(&res).unwrap()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::contract::{execute, query};
+    use crate::msg::{ExecuteMsg, QueryMsg};
+    use crate::state::{
+        CalldataVariant, Payment, SymbolFailures, KEEPER_REWARDS, REWARD_POOL, TREASURY,
+    };
+    use crate::testing::{
+        attribute, bank_sends, price_result, receive, register_job, response_packet, run,
+        sent_packets, setup, update_job, update_job_at, CHANNEL,
+    };
+    use cosmwasm_std::testing::{
+        mock_env, mock_ibc_packet_ack, mock_ibc_packet_recv, mock_ibc_packet_timeout, mock_info,
+    };
+    use cosmwasm_std::{coin, coins, from_slice, Deps, IbcAcknowledgement, Uint128};
+
+    #[test]
+    fn update_price_escrow_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        let free_job_id = register_job(deps.as_mut(), 1, &["ETH"]);
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetUpdatePrice {
+                job_id: job_id.clone(),
+                update_price: Some(coin(100, "uluna")),
+            },
+        )
+        .unwrap();
+
+        // The update price must be attached exactly, and free jobs accept no funds
+        let update = |job_id: &str| ExecuteMsg::UpdateJobData {
+            job_id: job_id.to_string(),
+        };
+        assert!(matches!(
+            run(deps.as_mut(), "keeper", &[], update(&job_id)),
+            Err(ContractError::InvalidFunds { .. })
+        ));
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "keeper",
+                &coins(99, "uluna"),
+                update(&job_id)
+            ),
+            Err(ContractError::InvalidFunds { .. })
+        ));
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "keeper",
+                &coins(1, "uluna"),
+                update(&free_job_id)
+            ),
+            Err(ContractError::NonPayable {})
+        ));
+
+        // A request that fails on BandChain refunds its escrow
+        let res = run(
+            deps.as_mut(),
+            "keeper",
+            &coins(100, "uluna"),
+            update(&job_id),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        assert_eq!(client_job_id(&request.client_id), job_id);
+        let pending = PENDING_REQUESTS.load(&deps.storage, &job_id).unwrap();
+        assert_eq!(pending.client_id, request.client_id);
+        assert_eq!(pending.payment, Some(Payment::Native(coin(100, "uluna"))));
+        assert!(matches!(
+            run(
+                deps.as_mut(),
+                "keeper",
+                &coins(100, "uluna"),
+                update(&job_id)
+            ),
+            Err(ContractError::RequestInFlight { .. })
+        ));
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &request.client_id,
+                1,
+                "RESOLVE_STATUS_FAILURE",
+                String::new(),
+            ),
+        );
+        assert_ne!(res.acknowledgement, make_ack_success());
+        assert_eq!(
+            bank_sends(&res.messages),
+            vec![("keeper".to_string(), coins(100, "uluna"))]
+        );
+        assert!(!PENDING_REQUESTS.has(&deps.storage, &job_id));
+
+        // So does a request whose packet timed out
+        let res = run(
+            deps.as_mut(),
+            "keeper",
+            &coins(100, "uluna"),
+            update(&job_id),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let res = ibc_packet_timeout(
+            deps.as_mut(),
+            mock_env(),
+            mock_ibc_packet_timeout(CHANNEL, &request).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            bank_sends(&res.messages),
+            vec![("keeper".to_string(), coins(100, "uluna"))]
+        );
+
+        // And a request rejected by BandChain
+        let res = run(
+            deps.as_mut(),
+            "keeper",
+            &coins(100, "uluna"),
+            update(&job_id),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let ack = IbcAcknowledgement::new(make_ack_fail("invalid calldata".to_string()));
+        let res = ibc_packet_ack(
+            deps.as_mut(),
+            mock_env(),
+            mock_ibc_packet_ack(CHANNEL, &request, ack).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            bank_sends(&res.messages),
+            vec![("keeper".to_string(), coins(100, "uluna"))]
+        );
+        assert!(TREASURY.may_load(&deps.storage, "uluna").unwrap().is_none());
+
+        // Only a successful request is paid for
+        let res = run(
+            deps.as_mut(),
+            "keeper",
+            &coins(100, "uluna"),
+            update(&job_id),
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &request.client_id,
+                2,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[5000000]),
+            ),
+        );
+        assert_eq!(res.acknowledgement, make_ack_success());
+        assert!(bank_sends(&res.messages).is_empty());
+        assert_eq!(
+            TREASURY.load(&deps.storage, "uluna").unwrap(),
+            Uint128::new(100)
+        );
+        assert_eq!(
+            PRICES.load(&deps.storage, "BTC").unwrap().rate,
+            Decimal::from_ratio(5u64, 1u64)
+        );
+    }
+
+    #[test]
+    fn replaced_request_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetUpdatePrice {
+                job_id: job_id.clone(),
+                update_price: Some(coin(100, "uluna")),
+            },
+        )
+        .unwrap();
+        let update = ExecuteMsg::UpdateJobData {
+            job_id: job_id.clone(),
+        };
+
+        // A request that never came back is replaced once it expired, refunding its escrow
+        let res = run(deps.as_mut(), "alice", &coins(100, "uluna"), update.clone()).unwrap();
+        let stale = sent_packets(&res).remove(0);
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(3600);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bob", &coins(100, "uluna")),
+            update,
+        )
+        .unwrap();
+        let replacement = sent_packets(&res).remove(0);
+        assert_ne!(replacement.client_id, stale.client_id);
+        assert_eq!(
+            bank_sends(&res.messages),
+            vec![("alice".to_string(), coins(100, "uluna"))]
+        );
+
+        // Late packets of the replaced request neither settle nor refund the replacement
+        let res = ibc_packet_timeout(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_packet_timeout(CHANNEL, &stale).unwrap(),
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let ack = IbcAcknowledgement::new(make_ack_fail("invalid calldata".to_string()));
+        let res = ibc_packet_ack(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_packet_ack(CHANNEL, &stale, ack).unwrap(),
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let res = receive(
+            deps.as_mut(),
+            env.clone(),
+            &response_packet(
+                &stale.client_id,
+                1,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[1000000]),
+            ),
+        );
+        assert_ne!(res.acknowledgement, make_ack_success());
+        assert!(PRICES.may_load(&deps.storage, "BTC").unwrap().is_none());
+        assert_eq!(
+            PENDING_REQUESTS
+                .load(&deps.storage, &job_id)
+                .unwrap()
+                .client_id,
+            replacement.client_id
+        );
+
+        // The replacement settles with its own response
+        let res = receive(
+            deps.as_mut(),
+            env,
+            &response_packet(
+                &replacement.client_id,
+                2,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[2000000]),
+            ),
+        );
+        assert_eq!(res.acknowledgement, make_ack_success());
+        assert!(!PENDING_REQUESTS.has(&deps.storage, &job_id));
+        assert_eq!(
+            TREASURY.load(&deps.storage, "uluna").unwrap(),
+            Uint128::new(100)
+        );
+        assert_eq!(
+            PRICES.load(&deps.storage, "BTC").unwrap().rate,
+            Decimal::from_ratio(2u64, 1u64)
+        );
+    }
+
+    #[test]
+    fn keeper_reward_test() {
+        let mut deps = setup();
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetKeeperReward {
+                keeper_reward: Some(coin(10, "uluna")),
+            },
+        )
+        .unwrap();
+        run(
+            deps.as_mut(),
+            "funder",
+            &coins(25, "uluna"),
+            ExecuteMsg::FundRewards {},
+        )
+        .unwrap();
+        let keeper = Addr::unchecked("keeper");
+        let claimable = |storage: &dyn Storage| {
+            KEEPER_REWARDS
+                .may_load(storage, (&keeper, "uluna"))
+                .unwrap()
+                .map(|rewards| rewards.claimable.u128())
+                .unwrap_or_default()
+        };
+        let mut request_id = 0;
+        let mut update = |mut deps: DepsMut, elapsed: u64, job_id: &str, resolve_status: &str| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(elapsed);
+            let client_id = update_job_at(deps.branch(), env.clone(), job_id);
+            request_id += 1;
+            receive(
+                deps,
+                env,
+                &response_packet(
+                    &client_id,
+                    request_id,
+                    resolve_status,
+                    price_result(&[1000000]),
+                ),
+            );
+        };
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetUpdateInterval {
+                job_id: job_id.clone(),
+                interval: Some(60),
+            },
+        )
+        .unwrap();
+
+        // A successful update of a due job earns the reward out of the pool
+        update(deps.as_mut(), 0, &job_id, "RESOLVE_STATUS_SUCCESS");
+        assert_eq!(claimable(&deps.storage), 10);
+        assert_eq!(
+            REWARD_POOL.load(&deps.storage, "uluna").unwrap(),
+            Uint128::new(15)
+        );
+
+        // Updating the job before it is due again earns nothing
+        update(deps.as_mut(), 30, &job_id, "RESOLVE_STATUS_SUCCESS");
+        assert_eq!(claimable(&deps.storage), 10);
+
+        // Neither does a failed update, nor a job without an update interval
+        update(deps.as_mut(), 90, &job_id, "RESOLVE_STATUS_FAILURE");
+        let unscheduled = register_job(deps.as_mut(), 2, &["ETH"]);
+        update(deps.as_mut(), 90, &unscheduled, "RESOLVE_STATUS_SUCCESS");
+        assert_eq!(claimable(&deps.storage), 10);
+
+        // Nor does a success once the pool cannot cover the reward
+        update(deps.as_mut(), 90, &job_id, "RESOLVE_STATUS_SUCCESS");
+        update(deps.as_mut(), 150, &job_id, "RESOLVE_STATUS_SUCCESS");
+        assert_eq!(claimable(&deps.storage), 20);
+        assert_eq!(
+            REWARD_POOL.load(&deps.storage, "uluna").unwrap(),
+            Uint128::new(5)
+        );
+
+        let res = run(deps.as_mut(), "keeper", &[], ExecuteMsg::ClaimRewards {}).unwrap();
+        assert_eq!(
+            bank_sends(&res.messages),
+            vec![("keeper".to_string(), coins(20, "uluna"))]
+        );
+        assert_eq!(claimable(&deps.storage), 0);
+        assert!(matches!(
+            run(deps.as_mut(), "keeper", &[], ExecuteMsg::ClaimRewards {}),
+            Err(ContractError::NoRewards {})
+        ));
+    }
+
+    #[test]
+    fn batch_update_test() {
+        let mut deps = setup();
+        let paid_job_ids = [
+            register_job(deps.as_mut(), 1, &["BTC"]),
+            register_job(deps.as_mut(), 1, &["ETH"]),
+        ];
+        for job_id in paid_job_ids.iter() {
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                ExecuteMsg::SetUpdatePrice {
+                    job_id: job_id.clone(),
+                    update_price: Some(coin(100, "uluna")),
+                },
+            )
+            .unwrap();
+        }
+        let free_job_id = register_job(deps.as_mut(), 1, &["ATOM"]);
+        let in_flight_job_id = register_job(deps.as_mut(), 1, &["DOT"]);
+        update_job(deps.as_mut(), &in_flight_job_id);
+        let cooling_job_id = register_job(deps.as_mut(), 1, &["LUNA"]);
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetUpdateInterval {
+                job_id: cooling_job_id.clone(),
+                interval: Some(60),
+            },
+        )
+        .unwrap();
+        let client_id = update_job(deps.as_mut(), &cooling_job_id);
+        receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &client_id,
+                1,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[1000000]),
+            ),
+        );
+
+        // Jobs that cannot be updated are skipped, and unspent funds go back to the caller
+        let res = run(
+            deps.as_mut(),
+            "keeper",
+            &coins(150, "uluna"),
+            ExecuteMsg::UpdateJobsData {
+                job_ids: vec![
+                    paid_job_ids[0].clone(),
+                    paid_job_ids[1].clone(),
+                    free_job_id.clone(),
+                    in_flight_job_id.clone(),
+                    cooling_job_id.clone(),
+                    "tvl-99".to_string(),
+                ],
+                merge: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            attribute(&res, "job_ids"),
+            format!("{},{}", paid_job_ids[0], free_job_id)
+        );
+        assert_eq!(
+            attribute(&res, "skipped"),
+            format!(
+                "{}:insufficient_funds,{}:in_flight,{}:cooldown,tvl-99:not_found",
+                paid_job_ids[1], in_flight_job_id, cooling_job_id
+            )
+        );
+        let packets = sent_packets(&res);
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| client_job_id(&packet.client_id).to_string())
+                .collect::<Vec<_>>(),
+            vec![paid_job_ids[0].clone(), free_job_id.clone()]
+        );
+        assert_eq!(
+            bank_sends(&res.messages),
+            vec![("keeper".to_string(), coins(50, "uluna"))]
+        );
+        assert!(!PENDING_REQUESTS.has(&deps.storage, &paid_job_ids[1]));
+
+        // The escrow of each batched job is refunded on its own
+        let res = ibc_packet_timeout(
+            deps.as_mut(),
+            mock_env(),
+            mock_ibc_packet_timeout(CHANNEL, &packets[0]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            bank_sends(&res.messages),
+            vec![("keeper".to_string(), coins(100, "uluna"))]
+        );
+        assert!(PENDING_REQUESTS.has(&deps.storage, &free_job_id));
+
+        // Once due again, the job is no longer skipped
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("keeper", &[]),
+            ExecuteMsg::UpdateJobsData {
+                job_ids: vec![cooling_job_id.clone()],
+                merge: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(attribute(&res, "job_ids"), cooling_job_id);
+    }
+
+    #[test]
+    fn merged_batch_test() {
+        let mut deps = setup();
+        let btc_job_id = register_job(deps.as_mut(), 1, &["BTC", "ETH"]);
+        let atom_job_id = register_job(deps.as_mut(), 1, &["ETH", "ATOM"]);
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetKeeperReward {
+                keeper_reward: Some(coin(10, "uluna")),
+            },
+        )
+        .unwrap();
+        run(
+            deps.as_mut(),
+            "funder",
+            &coins(100, "uluna"),
+            ExecuteMsg::FundRewards {},
+        )
+        .unwrap();
+        for job_id in [&btc_job_id, &atom_job_id] {
+            run(
+                deps.as_mut(),
+                "owner",
+                &[],
+                ExecuteMsg::SetUpdateInterval {
+                    job_id: job_id.clone(),
+                    interval: Some(60),
+                },
+            )
+            .unwrap();
+        }
+        let merged_update = |deps: DepsMut, elapsed: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(elapsed);
+            let res = execute(
+                deps,
+                env,
+                mock_info("keeper", &[]),
+                ExecuteMsg::UpdateJobsData {
+                    job_ids: vec![btc_job_id.clone(), atom_job_id.clone()],
+                    merge: true,
+                },
+            )
+            .unwrap();
+            sent_packets(&res)
+        };
+        let claimable = |storage: &dyn Storage| {
+            KEEPER_REWARDS
+                .load(storage, (&Addr::unchecked("keeper"), "uluna"))
+                .unwrap()
+                .claimable
+                .u128()
+        };
+
+        // Jobs sharing their request parameters are requested once, for the union of their symbols
+        let packets = merged_update(deps.as_mut(), 0);
+        assert_eq!(packets.len(), 1);
+        let client_id = packets[0].client_id.clone();
+        assert!(client_id.starts_with("batch-"));
+        assert_eq!(
+            BATCH_REQUESTS.load(&deps.storage, &client_id).unwrap(),
+            BatchRequest {
+                job_ids: vec![btc_job_id.clone(), atom_job_id.clone()],
+                symbols: vec!["BTC".to_string(), "ETH".to_string(), "ATOM".to_string()],
+            }
+        );
+
+        // The result fans out to every merged job, earning a single keeper reward
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &client_id,
+                100,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[1000000, 2000000, 3000000]),
+            ),
+        );
+        assert_eq!(res.acknowledgement, make_ack_success());
+        let rate = |storage: &dyn Storage, symbol: &str| PRICES.load(storage, symbol).unwrap().rate;
+        assert_eq!(rate(&deps.storage, "BTC"), Decimal::one());
+        assert_eq!(
+            rate(&deps.storage, "ETH"),
+            Decimal::from_ratio(2u128, 1u128)
+        );
+        assert_eq!(
+            rate(&deps.storage, "ATOM"),
+            Decimal::from_ratio(3u128, 1u128)
+        );
+        assert!(!BATCH_REQUESTS.has(&deps.storage, &client_id));
+        for job_id in [&btc_job_id, &atom_job_id] {
+            assert!(!PENDING_REQUESTS.has(&deps.storage, job_id));
+        }
+        assert_eq!(claimable(&deps.storage), 10);
+
+        // A job symbol missing from the batch keeps its previous price instead of failing the packet
+        let client_id = merged_update(deps.as_mut(), 60).remove(0).client_id;
+        let mut batch = BATCH_REQUESTS.load(&deps.storage, &client_id).unwrap();
+        batch.symbols.pop();
+        BATCH_REQUESTS
+            .save(&mut deps.storage, &client_id, &batch)
+            .unwrap();
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &client_id,
+                101,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[4000000, 5000000]),
+            ),
+        );
+        assert_eq!(res.acknowledgement, make_ack_success());
+        assert_eq!(
+            rate(&deps.storage, "BTC"),
+            Decimal::from_ratio(4u128, 1u128)
+        );
+        assert_eq!(
+            rate(&deps.storage, "ETH"),
+            Decimal::from_ratio(5u128, 1u128)
+        );
+        assert_eq!(
+            rate(&deps.storage, "ATOM"),
+            Decimal::from_ratio(3u128, 1u128)
+        );
+        assert_eq!(claimable(&deps.storage), 20);
+
+        // A response to a batch whose jobs were all replaced since is dropped
+        let stale_client_id = merged_update(deps.as_mut(), 120).remove(0).client_id;
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(120 + 3600);
+        for job_id in [&btc_job_id, &atom_job_id] {
+            update_job_at(deps.as_mut(), env.clone(), job_id);
+        }
+        let res = receive(
+            deps.as_mut(),
+            env,
+            &response_packet(
+                &stale_client_id,
+                102,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[6000000, 7000000, 8000000]),
+            ),
+        );
+        assert_ne!(res.acknowledgement, make_ack_success());
+        assert_eq!(
+            rate(&deps.storage, "BTC"),
+            Decimal::from_ratio(4u128, 1u128)
+        );
+        assert_eq!(claimable(&deps.storage), 20);
+    }
+
+    #[test]
+    fn reprocess_result_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        run(
+            deps.as_mut(),
+            "owner",
+            &[],
+            ExecuteMsg::SetResultRetention {
+                result_retention: 2,
+            },
+        )
+        .unwrap();
+        for request_id in 1..=3 {
+            let packet = response_packet(
+                &update_job(deps.as_mut(), &job_id),
+                request_id,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[request_id * 1000000]),
+            );
+            receive(deps.as_mut(), mock_env(), &packet);
+        }
+
+        // Only the latest results are kept
+        let request_ids = RAW_RESULTS
+            .prefix(&job_id)
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<u64>>>()
+            .unwrap();
+        assert_eq!(request_ids, vec![2, 3]);
+
+        let reprocess = |request_id: u64| ExecuteMsg::ReprocessResult {
+            job_id: job_id.clone(),
+            request_id,
+        };
+        assert!(matches!(
+            run(deps.as_mut(), "keeper", &[], reprocess(3)),
+            Err(ContractError::Unauthorized {})
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "owner", &[], reprocess(1)),
+            Err(ContractError::ResultNotFound { request_id: 1, .. })
+        ));
+        assert!(matches!(
+            run(deps.as_mut(), "owner", &[], reprocess(2)),
+            Err(ContractError::StaleResult { .. })
+        ));
+
+        // The latest result is decoded again with the job's current parameters
+        let mut job = JOBS.load(&deps.storage, &job_id).unwrap();
+        job.multiplier = Some(1000);
+        JOBS.save(&mut deps.storage, &job_id, &job).unwrap();
+        run(deps.as_mut(), "owner", &[], reprocess(3)).unwrap();
+        let price = PRICES.load(&deps.storage, "BTC").unwrap();
+        assert_eq!(price.rate, Decimal::from_ratio(3000u128, 1u128));
+        assert_eq!(price.bandchain_request_id, 3);
+    }
+
+    #[test]
+    fn malformed_response_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+
+        let mut trailing = base64::decode(price_result(&[100])).unwrap();
+        trailing.push(0);
+        let malformed = |client_id: &str| {
+            let mut bad_request_id =
+                response_packet(client_id, 1, "RESOLVE_STATUS_SUCCESS", price_result(&[100]));
+            bad_request_id.request_id = "one".to_string();
+            vec![
+                response_packet(client_id, 1, "RESOLVE_STATUS_SUCCESS", "%%%".to_string()),
+                response_packet(
+                    client_id,
+                    1,
+                    "RESOLVE_STATUS_SUCCESS",
+                    base64::encode(&trailing),
+                ),
+                response_packet(
+                    client_id,
+                    1,
+                    "RESOLVE_STATUS_SUCCESS",
+                    base64::encode([0u8]),
+                ),
+                bad_request_id,
+            ]
+        };
+
+        // Malformed responses fail the ack and settle the request instead of panicking
+        for index in 0..4 {
+            let client_id = update_job(deps.as_mut(), &job_id);
+            let packet = malformed(&client_id).remove(index);
+            let res = receive(deps.as_mut(), mock_env(), &packet);
+            assert_ne!(res.acknowledgement, make_ack_success());
+            assert!(!PENDING_REQUESTS.has(&deps.storage, &job_id));
+            assert!(PRICES.may_load(&deps.storage, "BTC").unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn ack_error_code_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC"]);
+        let ack_error = |res: &IbcReceiveResponse| -> AckError {
+            let error = match from_binary(&res.acknowledgement).unwrap() {
+                Ack::Error(error) => error,
+                Ack::Result(_) => panic!("expected a failure ack"),
+            };
+            let ack_error: AckError = from_slice(error.as_bytes()).unwrap();
+            assert!(res
+                .attributes
+                .contains(&attr("error_code", ack_error.code.as_str())));
+            ack_error
+        };
+
+        // Wrong channel
+        let client_id = update_job(deps.as_mut(), &job_id);
+        let packet = response_packet(
+            &client_id,
+            1,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[100]),
+        );
+        let res = ibc_packet_receive(
+            deps.as_mut(),
+            mock_env(),
+            mock_ibc_packet_recv("channel-9", &packet).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            ack_error(&res),
+            AckError {
+                code: AckErrorCode::WrongChannel,
+                message: "Received packet coming from the wrong channel".to_string(),
+            }
+        );
+
+        // Unknown client ID
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet("unknown", 1, "RESOLVE_STATUS_SUCCESS", price_result(&[100])),
+        );
+        assert_eq!(ack_error(&res).code, AckErrorCode::UnknownClientId);
+
+        // Unresolved status
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(&client_id, 1, "RESOLVE_STATUS_FAILURE", String::new()),
+        );
+        assert_eq!(ack_error(&res).code, AckErrorCode::UnresolvedStatus);
+
+        // Stale, as the request was settled by the previous response
+        let res = receive(deps.as_mut(), mock_env(), &packet);
+        assert_eq!(
+            ack_error(&res),
+            AckError {
+                code: AckErrorCode::Stale,
+                message: "Response to a request that is no longer pending".to_string(),
+            }
+        );
+
+        // Invalid result
+        let client_id = update_job(deps.as_mut(), &job_id);
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(&client_id, 2, "RESOLVE_STATUS_SUCCESS", "%%%".to_string()),
+        );
+        assert_eq!(ack_error(&res).code, AckErrorCode::InvalidResult);
+
+        // Length mismatch
+        let client_id = update_job(deps.as_mut(), &job_id);
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &client_id,
+                3,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[100, 200]),
+            ),
+        );
+        assert_eq!(ack_error(&res).code, AckErrorCode::LengthMismatch);
+
+        // Bounds violation, settling the request without storing any price
+        let client_id = update_job(deps.as_mut(), &job_id);
+        JOBS.update(&mut deps.storage, &job_id, |job| -> StdResult<_> {
+            let mut job = job.unwrap();
+            job.multiplier = Some(0);
+            Ok(job)
+        })
+        .unwrap();
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &client_id,
+                4,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[100]),
+            ),
+        );
+        assert_eq!(ack_error(&res).code, AckErrorCode::BoundsViolation);
+        assert!(!PENDING_REQUESTS.has(&deps.storage, &job_id));
+        assert!(PRICES.may_load(&deps.storage, "BTC").unwrap().is_none());
+
+        // Errors unrelated to the response are internal
+        assert_eq!(
+            AckErrorCode::from(&ContractError::Std(StdError::generic_err("failure"))),
+            AckErrorCode::Internal
+        );
+        assert_eq!(
+            AckErrorCode::from(&ContractError::Unauthorized {}),
+            AckErrorCode::Internal
+        );
+        assert_eq!(
+            AckErrorCode::from(&ContractError::StaleResult {
+                job_id: job_id.clone(),
+            }),
+            AckErrorCode::Stale
+        );
+    }
+
+    #[test]
+    fn calldata_variant_test() {
+        let mut deps = setup();
+        let register = |deps: DepsMut, symbols: &[&str], calldata_variant: CalldataVariant| {
+            run(
+                deps,
+                "owner",
+                &[],
+                ExecuteMsg::RegisterJob {
+                    oracle_script_id: 1,
+                    symbols: symbols.iter().map(|symbol| symbol.to_string()).collect(),
+                    multiplier: 1000000,
+                    ask_count: Some(4),
+                    min_count: Some(3),
+                    update_interval: None,
+                    calldata_variant: Some(calldata_variant),
+                },
+            )
+            .map(|res| attribute(&res, "job_id"))
+        };
+        let per_symbol = |multipliers: &[u64]| CalldataVariant::PerSymbolMultipliers {
+            multipliers: multipliers.to_vec(),
+        };
+
+        // Every symbol needs its own non-zero multiplier
+        for multipliers in [vec![100], vec![100, 1000, 10], vec![100, 0]] {
+            assert!(matches!(
+                register(deps.as_mut(), &["BTC", "ETH"], per_symbol(&multipliers)),
+                Err(ContractError::InvalidMultipliers {})
+            ));
+        }
+        let job_id = register(deps.as_mut(), &["BTC", "ETH"], per_symbol(&[100, 1000])).unwrap();
+        let client_id = update_job(deps.as_mut(), &job_id);
+        receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &client_id,
+                1,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[250, 3000]),
+            ),
+        );
+        let rate = |storage: &dyn Storage, symbol: &str| {
+            PRICES
+                .may_load(storage, symbol)
+                .unwrap()
+                .map(|price| price.rate)
+        };
+        assert_eq!(rate(&deps.storage, "BTC"), Some(Decimal::percent(250)));
+        assert_eq!(rate(&deps.storage, "ETH"), Some(Decimal::percent(300)));
+
+        // Standard dataset jobs request a minimum source count and skip unavailable symbols
+        let variant = CalldataVariant::SymbolsMinimumSourceCount {
+            minimum_source_count: 3,
+        };
+        let job_id = register(deps.as_mut(), &["BTC", "LUNA"], variant).unwrap();
+        let res = run(
+            deps.as_mut(),
+            "keeper",
+            &[],
+            ExecuteMsg::UpdateJobData {
+                job_id: job_id.clone(),
+            },
+        )
+        .unwrap();
+        let request = sent_packets(&res).remove(0);
+        let mut calldata = vec![0, 0, 0, 2, 0, 0, 0, 3];
+        calldata.extend_from_slice(b"BTC");
+        calldata.extend_from_slice(&[0, 0, 0, 4]);
+        calldata.extend_from_slice(b"LUNA");
+        calldata.push(3);
+        assert_eq!(request.calldata, calldata);
+
+        let mut result = vec![0, 0, 0, 2];
+        for (symbol, response_code, rate) in [("BTC", 0u8, 4_000_000_000u64), ("LUNA", 1, 0)] {
+            result.extend_from_slice(&(symbol.len() as u32).to_be_bytes());
+            result.extend_from_slice(symbol.as_bytes());
+            result.push(response_code);
+            result.extend_from_slice(&rate.to_be_bytes());
+        }
+        let res = receive(
+            deps.as_mut(),
+            mock_env(),
+            &response_packet(
+                &request.client_id,
+                2,
+                "RESOLVE_STATUS_SUCCESS",
+                base64::encode(result),
+            ),
+        );
+        assert_eq!(res.acknowledgement, make_ack_success());
+        assert_eq!(rate(&deps.storage, "BTC"), Some(Decimal::percent(400)));
+        assert_eq!(rate(&deps.storage, "LUNA"), None);
+    }
+
+    #[test]
+    fn unavailable_symbol_test() {
+        let mut deps = setup();
+        let job_id = register_job(deps.as_mut(), 1, &["BTC", "ETH"]);
+        let failures = |deps: Deps, symbol: &str| -> SymbolFailures {
+            from_binary(
+                &query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::SymbolFailures {
+                        symbol: symbol.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        let client_id = update_job(deps.as_mut(), &job_id);
+        let packet = response_packet(
+            &client_id,
+            1,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[1000000, 2000000]),
+        );
+        receive(deps.as_mut(), mock_env(), &packet);
+
+        // A symbol reported with a zero rate keeps its previous price and counts as a failure
+        for request_id in [2, 3] {
+            let client_id = update_job(deps.as_mut(), &job_id);
+            let packet = response_packet(
+                &client_id,
+                request_id,
+                "RESOLVE_STATUS_SUCCESS",
+                price_result(&[3000000, 0]),
+            );
+            let res = receive(deps.as_mut(), mock_env(), &packet);
+            assert_eq!(res.acknowledgement, make_ack_success());
+            assert!(res.attributes.contains(&attr("updated_symbols", "BTC")));
+            assert!(res.attributes.contains(&attr("failed_symbols", "ETH")));
+        }
+        assert_eq!(
+            PRICES
+                .load(&deps.storage, "BTC")
+                .unwrap()
+                .bandchain_request_id,
+            3
+        );
+        let eth = PRICES.load(&deps.storage, "ETH").unwrap();
+        assert_eq!(eth.rate, Decimal::percent(200));
+        assert_eq!(eth.bandchain_request_id, 1);
+        assert_eq!(
+            failures(deps.as_ref(), "ETH"),
+            SymbolFailures {
+                failures: 2,
+                consecutive_failures: 2,
+                last_failure: Some(mock_env().block.time.seconds()),
+            }
+        );
+        assert_eq!(failures(deps.as_ref(), "BTC"), SymbolFailures::default());
+
+        // An update of the symbol resets its consecutive failures
+        let client_id = update_job(deps.as_mut(), &job_id);
+        let packet = response_packet(
+            &client_id,
+            4,
+            "RESOLVE_STATUS_SUCCESS",
+            price_result(&[3000000, 4000000]),
+        );
+        receive(deps.as_mut(), mock_env(), &packet);
+        let eth = failures(deps.as_ref(), "ETH");
+        assert_eq!((eth.failures, eth.consecutive_failures), (2, 0));
+    }
+}
@@ -0,0 +1,442 @@
+use crate::ContractError;
+use std::fmt;
+
+/// The maximum nesting depth of arrays and objects, which bounds the recursion of the parser
+const MAX_DEPTH: usize = 64;
+
+/// ## Description
+/// This enum describes a JSON value. Numbers are kept as their decimal digits, so that integers of
+/// any size are represented exactly and no floating point is ever involved.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    /// An integer, as its decimal digits with an optional leading `-`
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// The members of an object, in order
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Parses a JSON document. Fractional and exponent numbers are rejected.
+    pub fn from_slice(data: &[u8]) -> Result<JsonValue, ContractError> {
+        let mut parser = Parser { data, pos: 0 };
+        let value = parser.parse_value(1)?;
+        parser.skip_whitespace();
+        if parser.pos != data.len() {
+            return Err(parser.error("trailing characters"));
+        }
+
+        Ok(value)
+    }
+
+    /// Writes the value as a compact JSON document.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.write(&mut out);
+        out
+    }
+
+    /// Returns the value of the specified member if the value is an object.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            JsonValue::Null => out.extend_from_slice(b"null"),
+            JsonValue::Bool(true) => out.extend_from_slice(b"true"),
+            JsonValue::Bool(false) => out.extend_from_slice(b"false"),
+            JsonValue::Number(digits) => out.extend_from_slice(digits.as_bytes()),
+            JsonValue::String(string) => write_string(string, out),
+            JsonValue::Array(items) => {
+                out.push(b'[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(b',');
+                    }
+                    item.write(out);
+                }
+                out.push(b']');
+            }
+            JsonValue::Object(members) => {
+                out.push(b'{');
+                for (i, (name, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        out.push(b',');
+                    }
+                    write_string(name, out);
+                    out.push(b':');
+                    value.write(out);
+                }
+                out.push(b'}');
+            }
+        }
+    }
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The written document only holds valid strings and ASCII, so it is valid UTF-8
+        f.write_str(&String::from_utf8_lossy(&self.to_vec()))
+    }
+}
+
+fn write_string(string: &str, out: &mut Vec<u8>) {
+    out.push(b'"');
+    for c in string.chars() {
+        match c {
+            '"' => out.extend_from_slice(b"\\\""),
+            '\\' => out.extend_from_slice(b"\\\\"),
+            '\n' => out.extend_from_slice(b"\\n"),
+            '\r' => out.extend_from_slice(b"\\r"),
+            '\t' => out.extend_from_slice(b"\\t"),
+            c if (c as u32) < 0x20 => {
+                out.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes())
+            }
+            c => {
+                let mut buf = [0u8; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    out.push(b'"');
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, reason: &str) -> ContractError {
+        ContractError::InvalidJson {
+            reason: format!("{} at position {}", reason, self.pos),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\n' | b'\r' | b'\t') = self.data.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.data.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ContractError> {
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn expect_literal(&mut self, literal: &[u8]) -> Result<(), ContractError> {
+        if !self.data[self.pos..].starts_with(literal) {
+            return Err(self.error("invalid literal"));
+        }
+        self.pos += literal.len();
+        Ok(())
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<JsonValue, ContractError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("maximum nesting depth exceeded"));
+        }
+        match self.peek() {
+            Some(b'n') => self.expect_literal(b"null").map(|_| JsonValue::Null),
+            Some(b't') => self.expect_literal(b"true").map(|_| JsonValue::Bool(true)),
+            Some(b'f') => self
+                .expect_literal(b"false")
+                .map(|_| JsonValue::Bool(false)),
+            Some(b'"') => self.parse_string().map(JsonValue::String),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = vec![];
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(items));
+                }
+                loop {
+                    items.push(self.parse_value(depth + 1)?);
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Array(items));
+                        }
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = vec![];
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                loop {
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected member name"));
+                    }
+                    let name = self.parse_string()?;
+                    self.expect(b':')?;
+                    members.push((name, self.parse_value(depth + 1)?));
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Object(members));
+                        }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, ContractError> {
+        let start = self.pos;
+        if self.data[self.pos] == b'-' {
+            self.pos += 1;
+        }
+        let digits_start = self.pos;
+        while let Some(b'0'..=b'9') = self.data.get(self.pos) {
+            self.pos += 1;
+        }
+        if self.pos == digits_start {
+            return Err(self.error("expected digits"));
+        }
+        if let Some(b'.' | b'e' | b'E') = self.data.get(self.pos) {
+            return Err(self.error("only integer numbers are supported"));
+        }
+
+        // The digits were checked to be ASCII above
+        let digits = String::from_utf8_lossy(&self.data[start..self.pos]).into_owned();
+        Ok(JsonValue::Number(digits))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ContractError> {
+        let hex = self
+            .data
+            .get(self.pos..self.pos + 4)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(hex)
+    }
+
+    fn parse_string(&mut self) -> Result<String, ContractError> {
+        self.expect(b'"')?;
+        let mut bytes = vec![];
+        loop {
+            let byte = match self.data.get(self.pos) {
+                Some(byte) => *byte,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = match self.data.get(self.pos) {
+                        Some(escaped) => *escaped,
+                        None => return Err(self.error("unterminated string")),
+                    };
+                    self.pos += 1;
+                    let c = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.parse_hex4()?;
+                            // Characters outside the basic plane are escaped as a surrogate pair
+                            if (0xD800..0xDC00).contains(&code) {
+                                self.expect_literal(b"\\u")?;
+                                let low = self.parse_hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("invalid surrogate pair"));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            char::from_u32(code).ok_or_else(|| self.error("invalid character"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut buf = [0u8; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                byte if byte < 0x20 => return Err(self.error("control character in string")),
+                byte => bytes.push(byte),
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8 in string"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(data: &str) -> Result<JsonValue, ContractError> {
+        JsonValue::from_slice(data.as_bytes())
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(
+            parse(r#" {"a": [1, -20, 18446744073709551616000], "b": {"c": null}, "d": true, "e": false} "#)
+                .unwrap(),
+            JsonValue::Object(vec![
+                (
+                    "a".to_string(),
+                    JsonValue::Array(vec![
+                        JsonValue::Number("1".to_string()),
+                        JsonValue::Number("-20".to_string()),
+                        JsonValue::Number("18446744073709551616000".to_string()),
+                    ])
+                ),
+                (
+                    "b".to_string(),
+                    JsonValue::Object(vec![("c".to_string(), JsonValue::Null)])
+                ),
+                ("d".to_string(), JsonValue::Bool(true)),
+                ("e".to_string(), JsonValue::Bool(false)),
+            ])
+        );
+        assert_eq!(parse("[]").unwrap(), JsonValue::Array(vec![]));
+        assert_eq!(parse("{}").unwrap(), JsonValue::Object(vec![]));
+    }
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(
+            parse(r#""\"\\\/\b\f\n\r\t\u0041\u00e9""#).unwrap(),
+            JsonValue::String("\"\\/\u{8}\u{c}\n\r\tA\u{e9}".to_string())
+        );
+        assert_eq!(
+            parse("\"caf\u{e9} \u{1f600}\"").unwrap(),
+            JsonValue::String("caf\u{e9} \u{1f600}".to_string())
+        );
+        for invalid in [
+            r#""\x""#,
+            r#""\u12""#,
+            r#""\u+123""#,
+            r#""unterminated"#,
+            "\"line\nbreak\"",
+        ] {
+            assert!(matches!(
+                parse(invalid),
+                Err(ContractError::InvalidJson { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn surrogate_pair_test() {
+        assert_eq!(
+            parse(r#""\ud83d\ude00""#).unwrap(),
+            JsonValue::String("\u{1f600}".to_string())
+        );
+        // Unpaired and reversed surrogates are rejected
+        for invalid in [
+            r#""\ud83d""#,
+            r#""\ud83dx""#,
+            r#""\ud83d\u0041""#,
+            r#""\ude00""#,
+            r#""\ude00\ud83d""#,
+        ] {
+            assert!(matches!(
+                parse(invalid),
+                Err(ContractError::InvalidJson { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn number_test() {
+        for invalid in ["1.5", "-0.1", "1e3", "1E3", "2e-1", "-", "-a", "+1"] {
+            assert!(matches!(
+                parse(invalid),
+                Err(ContractError::InvalidJson { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn depth_limit_test() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(matches!(
+            parse(&nested(MAX_DEPTH + 1)),
+            Err(ContractError::InvalidJson { .. })
+        ));
+        assert!(matches!(
+            parse(&format!(
+                "{}1{}",
+                r#"{"a":"#.repeat(MAX_DEPTH + 1),
+                "}".repeat(MAX_DEPTH + 1)
+            )),
+            Err(ContractError::InvalidJson { .. })
+        ));
+    }
+
+    #[test]
+    fn trailing_garbage_test() {
+        for invalid in ["1 2", "{} x", "[1],", "nullx", "true false", "[1]]"] {
+            assert!(matches!(
+                parse(invalid),
+                Err(ContractError::InvalidJson { .. })
+            ));
+        }
+        assert!(parse(" [1] \n\t").is_ok());
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let value = JsonValue::Object(vec![
+            (
+                "say \"hi\"\n".to_string(),
+                JsonValue::String("tab\t\u{1} \u{e9} \u{1f600} back\\slash".to_string()),
+            ),
+            (
+                "values".to_string(),
+                JsonValue::Array(vec![
+                    JsonValue::Null,
+                    JsonValue::Bool(true),
+                    JsonValue::Number("-340282366920938463463374607431768211456".to_string()),
+                    JsonValue::Object(vec![]),
+                ]),
+            ),
+        ]);
+        let encoded = value.to_vec();
+        assert_eq!(JsonValue::from_slice(&encoded).unwrap(), value);
+        assert_eq!(JsonValue::from_slice(&encoded).unwrap().to_vec(), encoded);
+        assert_eq!(
+            String::from_utf8(JsonValue::String("\u{1}".to_string()).to_vec()).unwrap(),
+            r#""\u0001""#
+        );
+    }
+}
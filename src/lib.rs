@@ -1,8 +1,16 @@
+pub mod aggregation;
 pub mod contract;
 mod error;
+pub mod history;
 pub mod ibc;
+pub mod json;
 pub mod msg;
 pub mod obi;
+pub mod payment;
+pub mod randomness;
+pub mod reward;
 pub mod state;
+#[cfg(test)]
+mod testing;
 
 pub use crate::error::ContractError;
@@ -1,7 +1,13 @@
-use cosmwasm_std::Coin;
+use cosmwasm_std::{Binary, Coin, Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::state::{
+    AggregationMethod, CalldataVariant, DefaultRequest, OracleScript, PriceData, PriceSource,
+    VrfRequest,
+};
+
 /// ## Description
 /// This structure stores the basic settings for creating a new contract instance.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -29,15 +35,335 @@ pub enum ExecuteMsg {
         /// The multiplier to use to multiply the oracle price by.
         multiplier: u64,
         /// The number of BandChain validators that are requested to respond to this  oracle request.
-        ask_count: u64,
+        /// Defaults to the registered oracle script's ask count.
+        ask_count: Option<u64>,
+        /// The minimum number of validators necessary for the request to proceed to the execution phase.
+        /// Defaults to the registered oracle script's min count.
+        min_count: Option<u64>,
+        /// The target interval (in seconds) between successful updates. Unscheduled jobs are never due.
+        update_interval: Option<u64>,
+        /// The calldata layout expected by the oracle script. Defaults to symbols and a multiplier.
+        calldata_variant: Option<CalldataVariant>,
+    },
+    /// Register a new oracle request job for an oracle script returning arbitrary data.
+    RegisterGenericJob {
+        /// ID of the oracle script on BandChain to query the data from
+        oracle_script_id: u64,
+        /// The OBI schema of the oracle script. Defaults to the registered oracle script's schema.
+        schema: Option<String>,
+        /// The calldata of the requests, as a JSON document matching the input schema.
+        calldata: String,
+        /// The number of BandChain validators that are requested to respond to this  oracle request.
+        /// Defaults to the registered oracle script's ask count.
+        ask_count: Option<u64>,
         /// The minimum number of validators necessary for the request to proceed to the execution phase.
-        min_count: u64,
+        /// Defaults to the registered oracle script's min count.
+        min_count: Option<u64>,
+        /// The target interval (in seconds) between successful updates. Unscheduled jobs are never due.
+        update_interval: Option<u64>,
     },
     /// Request and update oracle data for the specified request job ID.
     UpdateJobData {
         /// The ID of the oracle request job to update.
         job_id: String,
     },
+    /// Request and update oracle data for each of the specified request job IDs,
+    /// skipping the jobs that cannot be updated right now.
+    UpdateJobsData {
+        /// The IDs of the oracle request jobs to update.
+        job_ids: Vec<String>,
+        /// Whether to merge the jobs sharing the same request parameters into a single request
+        #[serde(default)]
+        merge: bool,
+    },
+    /// Request and update oracle data for the jobs that are due for an update.
+    UpdateAllDueJobs {
+        /// The maximum number of jobs to update
+        limit: Option<u32>,
+        /// Whether to merge the jobs sharing the same request parameters into a single request
+        #[serde(default)]
+        merge: bool,
+    },
+    /// Set the policy used to combine the prices reported by multiple jobs for a symbol.
+    SetAggregation {
+        /// The symbol of the asset to set the aggregation policy for
+        symbol: String,
+        /// The method used to combine the source prices
+        method: AggregationMethod,
+        /// The minimum number of fresh sources required to publish a new price
+        min_sources: u64,
+        /// The maximum age (in seconds) of a source price for it to be considered fresh
+        max_age: u64,
+    },
+    /// Remove the aggregation policy of a symbol, publishing the latest reported price again.
+    RemoveAggregation {
+        /// The symbol of the asset to remove the aggregation policy for
+        symbol: String,
+    },
+    /// Set the ordered list of jobs the price of a symbol is served from. An empty list removes it.
+    SetPriceSources {
+        /// The symbol of the asset to set the price sources for
+        symbol: String,
+        /// The jobs to serve the price from, in order of preference
+        sources: Vec<PriceSource>,
+    },
+    /// Pin the price of a symbol until the given expiry, shadowing the oracle price.
+    OverridePrice {
+        /// The symbol of the asset to pin the price for
+        symbol: String,
+        /// The price to serve while the override is active
+        rate: Decimal,
+        /// The UNIX epoch time (in seconds) at which the override expires, at most a week away
+        expires_at: u64,
+    },
+    /// Remove the price override of a symbol before it expires.
+    RemovePriceOverride {
+        /// The symbol of the asset to remove the override for
+        symbol: String,
+    },
+    /// Subscribe the calling contract to price updates of the specified symbols.
+    Subscribe {
+        /// The symbols to receive price updates for
+        symbols: Vec<String>,
+    },
+    /// Unsubscribe the calling contract from price updates of the specified symbols.
+    Unsubscribe {
+        /// The symbols to stop receiving price updates for
+        symbols: Vec<String>,
+    },
+    /// Allow or disallow a contract to make one-off price and randomness requests.
+    SetRequester {
+        /// The address of the contract
+        address: String,
+        /// Whether the contract is allowed to make one-off price and randomness requests
+        allowed: bool,
+    },
+    /// Request fresh prices for the specified symbols, delivered back to the calling contract.
+    RequestPrices(PriceRequest),
+    /// Set the funds callers must attach to request an update of a job. `None` makes updates free.
+    SetUpdatePrice {
+        /// The ID of the oracle request job to set the update price for.
+        job_id: String,
+        /// The funds to attach to each update request
+        update_price: Option<Coin>,
+    },
+    /// Withdraw the update fees collected by the contract.
+    WithdrawFees {
+        /// The denom of the fees to withdraw
+        denom: String,
+        /// The amount to withdraw. Defaults to the whole collected balance.
+        amount: Option<Uint128>,
+        /// The address to send the fees to. Defaults to the owner.
+        recipient: Option<String>,
+    },
+    /// Set the reward paid to the address that triggered a successful update of a due job. `None` disables rewards.
+    SetKeeperReward {
+        /// The reward paid for each successful request updating due jobs
+        keeper_reward: Option<Coin>,
+    },
+    /// Fund the keeper reward pool with the attached native coins.
+    FundRewards {},
+    /// Claim the keeper rewards earned by the caller.
+    ClaimRewards {},
+    /// Set the maximum number of due jobs requested by each [`SudoMsg::Tick`].
+    SetTickBudget {
+        /// The maximum number of jobs requested per tick
+        tick_budget: u32,
+    },
+    /// Set the target interval between successful updates of the specified job.
+    SetUpdateInterval {
+        /// The ID of the oracle request job to set the update interval for
+        job_id: String,
+        /// The target interval (in seconds) between successful updates. `None` stops scheduling the job.
+        interval: Option<u64>,
+    },
+    /// Set the jobs requested once an update of the specified job succeeds.
+    SetFollowUps {
+        /// The ID of the oracle request job to set the follow-ups for
+        job_id: String,
+        /// The IDs of the jobs to request after each successful update
+        follow_ups: Vec<String>,
+    },
+    /// Register an oracle script along with its schema and default request parameters.
+    SetOracleScript {
+        /// The ID of the oracle script on BandChain
+        oracle_script_id: u64,
+        /// The oracle script details
+        oracle_script: OracleScript,
+    },
+    /// Remove an oracle script from the registry.
+    RemoveOracleScript {
+        /// The ID of the oracle script on BandChain
+        oracle_script_id: u64,
+    },
+    /// Set the request parameters used to request randomness from the VRF oracle script.
+    SetVrfRequest {
+        /// The VRF request parameters, or `None` to disable randomness requests
+        vrf_request: Option<VrfRequest>,
+    },
+    /// Request verifiable randomness, delivered back to the sending contract.
+    RequestRandomness {
+        /// The seed the randomness is derived from
+        seed: Binary,
+    },
+    /// Set the request parameters used to update symbols that have no registered job.
+    SetDefaultRequest {
+        /// The default request parameters, or `None` to disable symbol updates
+        default_request: Option<DefaultRequest>,
+    },
+    /// Allow or disallow symbols to be updated without a registered job.
+    SetAllowedSymbols {
+        /// The list of asset symbols
+        symbols: Vec<String>,
+        /// Whether the symbols may be updated without a registered job
+        allowed: bool,
+    },
+    /// Request and update the prices of the specified symbols using the default request parameters.
+    /// Each symbol can only be requested once per cooldown, except by the owner.
+    UpdateSymbols {
+        /// The list of asset symbols to update
+        symbols: Vec<String>,
+        /// The oracle script ID to query the data from instead of the default one. Owner only.
+        oracle_script_id: Option<u64>,
+    },
+    /// Set the number of raw results kept for each job.
+    SetResultRetention {
+        /// The number of raw results kept per job, 0 to keep none
+        result_retention: u32,
+    },
+    /// Decode a stored raw result of a job again and republish the resulting prices.
+    ReprocessResult {
+        /// The ID of the oracle request job the result was received for
+        job_id: String,
+        /// The BandChain request ID that produced the result
+        request_id: u64,
+    },
+    /// Receives cw20 tokens paying for an oracle request, embedding a [`Cw20HookMsg`].
+    Receive(Cw20ReceiveMsg),
+    /// Set the amount of a cw20 token accepted as payment for an oracle request. `None` stops accepting the token.
+    SetCw20UpdatePrice {
+        /// The address of the cw20 token contract
+        token: String,
+        /// The amount of tokens to send with each request
+        update_price: Option<Uint128>,
+    },
+    /// Withdraw the cw20 update fees collected by the contract.
+    WithdrawCw20Fees {
+        /// The address of the cw20 token contract
+        token: String,
+        /// The amount to withdraw. Defaults to the whole collected balance.
+        amount: Option<Uint128>,
+        /// The address to send the fees to. Defaults to the owner.
+        recipient: Option<String>,
+    },
+}
+
+/// ## Description
+/// This structure describes the privileged messages the chain can send to the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    /// Send oracle requests for the due jobs, up to the configured tick budget.
+    Tick {},
+    /// Set the IBC channel to be used for the oracle requests.
+    SetChannel {
+        /// The channel name to use for the oracle requests
+        channel: String,
+    },
+    /// Set the contract owner.
+    SetOwner {
+        /// The new contract owner address
+        owner: String,
+    },
+}
+
+/// ## Description
+/// This structure describes the oracle requests that can be paid for with cw20 tokens.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Request and update oracle data for the specified request job ID.
+    UpdateJobData {
+        /// The ID of the oracle request job to update.
+        job_id: String,
+    },
+    /// Request fresh prices for the specified symbols, delivered back to the sending contract.
+    RequestPrices(PriceRequest),
+}
+
+/// ## Description
+/// This structure describes a one-off price request made by a consumer contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceRequest {
+    /// ID of the oracle script on BandChain to query the data from
+    pub oracle_script_id: u64,
+    /// The list of symbols to query the price for
+    pub symbols: Vec<String>,
+    /// The multiplier to use to multiply the oracle price by.
+    pub multiplier: u64,
+    /// The number of BandChain validators that are requested to respond to this  oracle request.
+    pub ask_count: u64,
+    /// The minimum number of validators necessary for the request to proceed to the execution phase.
+    pub min_count: u64,
+}
+
+/// ## Description
+/// This structure describes the execute messages sent to subscribed consumer contracts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriberExecuteMsg {
+    /// Notifies the subscriber of newly published prices for the symbols it subscribed to.
+    ReceivePriceUpdate {
+        /// The newly published prices
+        prices: Vec<PriceUpdate>,
+    },
+}
+
+/// ## Description
+/// This structure describes a newly published price sent to a subscribed consumer contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceUpdate {
+    /// The symbol of the asset
+    pub symbol: String,
+    /// The newly published price data
+    pub price: PriceData,
+}
+
+/// ## Description
+/// This structure describes the execute messages sent to contracts that made a one-off price request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RequesterExecuteMsg {
+    /// Delivers the prices resolved for a one-off price request.
+    ReceivePriceResponse {
+        /// The client ID returned when the request was made
+        client_id: String,
+        /// The resolved prices. Symbols that were unavailable on BandChain are left out.
+        prices: Vec<PriceUpdate>,
+    },
+    /// Notifies the requester that a one-off price request could not be resolved.
+    ReceivePriceFailure {
+        /// The client ID returned when the request was made
+        client_id: String,
+        /// The reason the request failed
+        error: String,
+    },
+    /// Delivers the verifiable randomness resolved for a randomness request.
+    ReceiveRandomness {
+        /// The request ID returned when the request was made
+        request_id: String,
+        /// The random hash produced by the VRF
+        hash: Binary,
+        /// The proof the hash was derived from the seed
+        proof: Binary,
+    },
+    /// Notifies the requester that a randomness request could not be resolved.
+    ReceiveRandomnessFailure {
+        /// The request ID returned when the request was made
+        request_id: String,
+        /// The reason the request failed
+        error: String,
+    },
 }
 
 /// ## Description
@@ -91,8 +417,59 @@ pub enum QueryMsg {
     Config {},
     /// Job returns information about the specified job using a custom [`Job`] structure.
     Job { job_id: String },
-    /// Price returns the latest price for the specified asset symbol using a custom [`PriceData`] structure.
+    /// Price returns the latest price for the specified asset symbol using a custom [`PriceResponse`] structure.
     Price { symbol: String },
+    /// Aggregation returns the aggregation policy of the specified asset symbol using a custom [`Aggregation`] structure.
+    Aggregation { symbol: String },
+    /// SourcePrices returns the latest price reported by each job for the specified asset symbol.
+    SourcePrices { symbol: String },
+    /// PriceSources returns the ordered list of jobs the price of the specified asset symbol is served from.
+    PriceSources { symbol: String },
+    /// Subscribers returns the addresses of the contracts subscribed to price updates of the specified asset symbol.
+    Subscribers { symbol: String },
+    /// PendingRequest returns the in-flight request of the specified job using a custom [`PendingRequest`] structure.
+    PendingRequest { job_id: String },
+    /// Treasury returns the update fees collected by the contract using a custom [`TreasuryResponse`] structure.
+    Treasury {},
+    /// Cw20UpdatePrices returns the cw20 tokens accepted as payment for oracle requests, with their price per request.
+    Cw20UpdatePrices {},
+    /// RewardPool returns the funds available in the keeper reward pool.
+    RewardPool {},
+    /// KeeperRewards returns the rewards earned by the specified keeper using a custom [`KeeperRewardsResponse`] structure.
+    KeeperRewards { keeper: String },
+    /// JobStatus returns the request and success timestamps of the specified job using a custom [`JobStatus`] structure.
+    JobStatus { job_id: String },
+    /// DueJobs returns the jobs whose update interval has elapsed and which have no request in flight, most overdue first.
+    DueJobs { limit: Option<u32> },
+    /// AllowedSymbols returns the symbols that may be updated without a registered job.
+    AllowedSymbols {},
+    /// JobHistory returns the latest finished requests of the specified job, oldest first.
+    JobHistory {
+        job_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// JobStats returns the request statistics of the specified job using a custom [`JobStatsResponse`] structure.
+    JobStats { job_id: String },
+    /// SymbolFailures returns the failure counters of the specified symbol using a custom [`SymbolFailures`] structure.
+    SymbolFailures { symbol: String },
+    /// Randomness returns the specified randomness request using a custom [`Randomness`] structure.
+    Randomness { request_id: String },
+    /// JobResult returns the latest result of the specified generic job using a custom [`JobResult`] structure.
+    JobResult { job_id: String },
+    /// RawResults returns the raw results kept for the specified job, oldest first.
+    RawResults { job_id: String },
+    /// OracleScripts returns the registered oracle scripts using a custom [`OracleScriptResponse`] structure.
+    OracleScripts {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// BandchainRequest returns the response received for the specified BandChain request ID using a custom
+    /// [`BandchainRequest`] structure.
+    BandchainRequest { request_id: u64 },
+    /// Provenance returns the current price of the specified asset symbol along with the BandChain requests
+    /// that produced it using a custom [`ProvenanceResponse`] structure.
+    Provenance { symbol: String },
 }
 
 /// ## Description
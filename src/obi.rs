@@ -1,7 +1,11 @@
+use crate::json::JsonValue;
+use crate::state::CalldataVariant;
 use crate::ContractError;
-use base64::decode as b64decode;
-use cosmwasm_std::StdResult;
+use base64::{decode as b64decode, encode as b64encode};
+use cosmwasm_std::Uint256;
 use obi::{OBIDecode, OBIEncode, OBISchema};
+use std::convert::TryFrom;
+use std::str::FromStr;
 
 #[derive(OBIEncode, OBISchema, Debug)]
 pub struct PriceDataInput {
@@ -23,12 +27,489 @@ pub struct PriceDataOutput {
 }
 
 impl PriceDataOutput {
-    pub fn decode_obi(encoded: &str) -> StdResult<PriceDataOutput> {
-        let res: PriceDataOutput =
-            OBIDecode::try_from_slice(b64decode(encoded).unwrap().as_slice()).unwrap();
+    pub fn decode_obi(encoded: &str) -> Result<PriceDataOutput, ContractError> {
+        decode_base64_obi(encoded)
+    }
+}
+
+#[derive(OBIEncode, OBISchema, Debug)]
+pub struct MinimumSourceCountInput {
+    pub symbols: Vec<String>,
+    pub minimum_source_count: u8,
+}
+
+#[derive(OBIEncode, OBISchema, Debug)]
+pub struct MultipliersInput {
+    pub symbols: Vec<String>,
+    pub multipliers: Vec<u64>,
+}
+
+#[derive(OBIDecode, OBISchema, Debug)]
+pub struct SymbolResponse {
+    pub symbol: String,
+    pub response_code: u8,
+    pub rate: u64,
+}
+
+#[derive(OBIDecode, OBISchema, Debug)]
+pub struct ResponsesOutput {
+    pub responses: Vec<SymbolResponse>,
+}
+
+impl CalldataVariant {
+    /// Encodes the calldata requesting the rates of the specified symbols.
+    pub fn encode_obi(
+        &self,
+        symbols: Vec<String>,
+        multiplier: u64,
+    ) -> Result<Vec<u8>, ContractError> {
+        let res = match self {
+            CalldataVariant::SymbolsMultiplier => OBIEncode::try_to_vec(&PriceDataInput {
+                symbol: symbols,
+                multiplier,
+            })?,
+            CalldataVariant::SymbolsMinimumSourceCount {
+                minimum_source_count,
+            } => OBIEncode::try_to_vec(&MinimumSourceCountInput {
+                symbols,
+                minimum_source_count: *minimum_source_count,
+            })?,
+            CalldataVariant::PerSymbolMultipliers { multipliers } => {
+                OBIEncode::try_to_vec(&MultipliersInput {
+                    symbols,
+                    multipliers: multipliers.clone(),
+                })?
+            }
+        };
 
         Ok(res)
     }
+
+    /// Decodes the rates of a result, in the order of the requested symbols.
+    /// Symbols missing from the responses or reported with a non-zero response code have no rate.
+    pub fn decode_rates(
+        &self,
+        symbols: &[String],
+        encoded: &str,
+    ) -> Result<Vec<Option<u64>>, ContractError> {
+        match self {
+            CalldataVariant::SymbolsMinimumSourceCount { .. } => {
+                let ResponsesOutput { responses } = decode_base64_obi(encoded)?;
+                Ok(symbols
+                    .iter()
+                    .map(|symbol| {
+                        responses
+                            .iter()
+                            .find(|response| &response.symbol == symbol)
+                            .filter(|response| response.response_code == 0)
+                            .map(|response| response.rate)
+                    })
+                    .collect())
+            }
+            _ => {
+                let PriceDataOutput { rates } = PriceDataOutput::decode_obi(encoded)?;
+                Ok(rates.into_iter().map(Some).collect())
+            }
+        }
+    }
+}
+
+/// Returns whether an output type holds price feed rates, either as a list of rates or as a list
+/// of per-symbol responses.
+pub fn is_price_output(output: &ObiType) -> bool {
+    let responses = ObiType::Vector(Box::new(ObiType::Struct(vec![
+        ("symbol".to_string(), ObiType::String),
+        ("response_code".to_string(), ObiType::U8),
+        ("rate".to_string(), ObiType::U64),
+    ])));
+    match output {
+        ObiType::Struct(fields) if fields.len() == 1 => {
+            fields[0].1 == ObiType::Vector(Box::new(ObiType::U64)) || fields[0].1 == responses
+        }
+        _ => false,
+    }
+}
+
+#[derive(OBIEncode, OBISchema, Debug)]
+pub struct VrfInput {
+    pub seed: Vec<u8>,
+    pub time: u64,
+    pub worker_contract: String,
+}
+
+impl VrfInput {
+    pub fn encode_obi(self) -> Result<Vec<u8>, ContractError> {
+        let res = OBIEncode::try_to_vec(&self)?;
+
+        Ok(res)
+    }
+}
+
+#[derive(OBIDecode, OBISchema, Debug)]
+pub struct VrfOutput {
+    pub proof: Vec<u8>,
+    pub result: Vec<u8>,
+}
+
+impl VrfOutput {
+    pub fn decode_obi(encoded: &str) -> Result<VrfOutput, ContractError> {
+        decode_base64_obi(encoded)
+    }
+}
+
+/// Decodes a base64 OBI-encoded value, failing if any bytes are left over.
+fn decode_base64_obi<T: OBIDecode>(encoded: &str) -> Result<T, ContractError> {
+    let bytes = b64decode(encoded).map_err(|e| ContractError::InvalidBase64 {
+        reason: e.to_string(),
+    })?;
+
+    let mut remaining = bytes.as_slice();
+    let res = T::decode(&mut remaining).map_err(|e| ContractError::ObiDecode {
+        reason: e.to_string(),
+    })?;
+    if !remaining.is_empty() {
+        return Err(ContractError::TrailingBytes {
+            count: remaining.len(),
+        });
+    }
+
+    Ok(res)
+}
+
+/// ## Description
+/// This enum describes an OBI type as written in the schema of a BandChain oracle script.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObiType {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    I8,
+    I16,
+    I32,
+    I64,
+    Bool,
+    String,
+    Bytes,
+    Vector(Box<ObiType>),
+    Struct(Vec<(String, ObiType)>),
+}
+
+/// ## Description
+/// This structure holds the calldata and result types of an oracle script, parsed from its
+/// schema string, e.g. `{symbols:[string],multiplier:u64}/{rates:[u64]}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObiSchema {
+    pub input: ObiType,
+    pub output: ObiType,
+}
+
+impl ObiSchema {
+    pub fn parse(schema: &str) -> Result<ObiSchema, ContractError> {
+        let (input, output) = match schema.split_once('/') {
+            Some(types) => types,
+            None => {
+                return Err(ContractError::InvalidObiSchema {
+                    reason: "expected input and output types separated by '/'".to_string(),
+                })
+            }
+        };
+
+        Ok(ObiSchema {
+            input: ObiType::parse(input)?,
+            output: ObiType::parse(output)?,
+        })
+    }
+
+    /// Encodes the calldata of a request from a JSON value.
+    pub fn encode_input(&self, value: &JsonValue) -> Result<Vec<u8>, ContractError> {
+        let mut out = vec![];
+        self.input.encode(value, &mut out)?;
+        Ok(out)
+    }
+
+    /// Decodes the base64 OBI-encoded result of a request into a JSON value.
+    pub fn decode_output(&self, encoded: &str) -> Result<JsonValue, ContractError> {
+        let bytes = b64decode(encoded).map_err(|e| ContractError::InvalidBase64 {
+            reason: e.to_string(),
+        })?;
+
+        let mut remaining = bytes.as_slice();
+        let value = self.output.decode(&mut remaining)?;
+        if !remaining.is_empty() {
+            return Err(ContractError::TrailingBytes {
+                count: remaining.len(),
+            });
+        }
+
+        Ok(value)
+    }
+}
+
+impl ObiType {
+    pub fn parse(schema: &str) -> Result<ObiType, ContractError> {
+        let schema: String = schema.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut remaining = schema.as_str();
+        let obi_type = Self::parse_type(&mut remaining)?;
+        if !remaining.is_empty() {
+            return Err(ContractError::InvalidObiSchema {
+                reason: format!("unexpected '{}'", remaining),
+            });
+        }
+
+        Ok(obi_type)
+    }
+
+    fn parse_type(schema: &mut &str) -> Result<ObiType, ContractError> {
+        let invalid = |reason: &str| ContractError::InvalidObiSchema {
+            reason: reason.to_string(),
+        };
+
+        if let Some(rest) = schema.strip_prefix('[') {
+            *schema = rest;
+            let item = Self::parse_type(schema)?;
+            *schema = schema
+                .strip_prefix(']')
+                .ok_or_else(|| invalid("expected ']'"))?;
+            return Ok(ObiType::Vector(Box::new(item)));
+        }
+
+        if let Some(rest) = schema.strip_prefix('{') {
+            *schema = rest;
+            let mut fields = vec![];
+            if let Some(rest) = schema.strip_prefix('}') {
+                *schema = rest;
+                return Ok(ObiType::Struct(fields));
+            }
+            loop {
+                let name = Self::parse_ident(schema);
+                if name.is_empty() {
+                    return Err(invalid("expected field name"));
+                }
+                *schema = schema
+                    .strip_prefix(':')
+                    .ok_or_else(|| invalid("expected ':'"))?;
+                fields.push((name.to_string(), Self::parse_type(schema)?));
+
+                if let Some(rest) = schema.strip_prefix(',') {
+                    *schema = rest;
+                } else if let Some(rest) = schema.strip_prefix('}') {
+                    *schema = rest;
+                    return Ok(ObiType::Struct(fields));
+                } else {
+                    return Err(invalid("expected ',' or '}'"));
+                }
+            }
+        }
+
+        let name = Self::parse_ident(schema);
+        Ok(match name {
+            "u8" => ObiType::U8,
+            "u16" => ObiType::U16,
+            "u32" => ObiType::U32,
+            "u64" => ObiType::U64,
+            "u128" => ObiType::U128,
+            "u256" => ObiType::U256,
+            "i8" => ObiType::I8,
+            "i16" => ObiType::I16,
+            "i32" => ObiType::I32,
+            "i64" => ObiType::I64,
+            "bool" => ObiType::Bool,
+            "string" => ObiType::String,
+            "bytes" => ObiType::Bytes,
+            "" => return Err(invalid("expected type")),
+            name => return Err(invalid(&format!("unknown type '{}'", name))),
+        })
+    }
+
+    fn parse_ident<'a>(schema: &mut &'a str) -> &'a str {
+        let end = schema
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(schema.len());
+        let (ident, rest) = schema.split_at(end);
+        *schema = rest;
+        ident
+    }
+
+    /// Encodes a JSON value of this type, appending it to `out`.
+    /// Integers may be given as JSON numbers or decimal strings, and bytes as base64 strings.
+    pub fn encode(&self, value: &JsonValue, out: &mut Vec<u8>) -> Result<(), ContractError> {
+        let mismatch = |expected: &str| ContractError::ObiEncode {
+            reason: format!("expected {}, got {:?}", expected, value),
+        };
+
+        match self {
+            ObiType::U8 => out.push(parse_int::<u8>(value)?),
+            ObiType::U16 => out.extend_from_slice(&parse_int::<u16>(value)?.to_be_bytes()),
+            ObiType::U32 => out.extend_from_slice(&parse_int::<u32>(value)?.to_be_bytes()),
+            ObiType::U64 => out.extend_from_slice(&parse_int::<u64>(value)?.to_be_bytes()),
+            ObiType::U128 => out.extend_from_slice(&parse_int::<u128>(value)?.to_be_bytes()),
+            ObiType::U256 => out.extend_from_slice(&parse_int::<Uint256>(value)?.to_be_bytes()),
+            ObiType::I8 => out.extend_from_slice(&parse_int::<i8>(value)?.to_be_bytes()),
+            ObiType::I16 => out.extend_from_slice(&parse_int::<i16>(value)?.to_be_bytes()),
+            ObiType::I32 => out.extend_from_slice(&parse_int::<i32>(value)?.to_be_bytes()),
+            ObiType::I64 => out.extend_from_slice(&parse_int::<i64>(value)?.to_be_bytes()),
+            ObiType::Bool => match value {
+                JsonValue::Bool(flag) => out.push(*flag as u8),
+                _ => return Err(mismatch("bool")),
+            },
+            ObiType::String => match value {
+                JsonValue::String(string) => encode_bytes(string.as_bytes(), out)?,
+                _ => return Err(mismatch("string")),
+            },
+            ObiType::Bytes => match value {
+                JsonValue::String(encoded) => {
+                    let bytes = b64decode(encoded).map_err(|e| ContractError::InvalidBase64 {
+                        reason: e.to_string(),
+                    })?;
+                    encode_bytes(&bytes, out)?
+                }
+                _ => return Err(mismatch("base64 string")),
+            },
+            ObiType::Vector(item) => match value {
+                JsonValue::Array(items) => {
+                    encode_len(items.len(), out)?;
+                    for value in items {
+                        item.encode(value, out)?;
+                    }
+                }
+                _ => return Err(mismatch("array")),
+            },
+            ObiType::Struct(fields) => {
+                if !matches!(value, JsonValue::Object(_)) {
+                    return Err(mismatch("object"));
+                }
+                for (name, field) in fields {
+                    match value.get(name) {
+                        Some(value) => field.encode(value, out)?,
+                        None => {
+                            return Err(ContractError::ObiEncode {
+                                reason: format!("missing field '{}'", name),
+                            })
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decodes a value of this type from the front of `buf` into a JSON value.
+    /// Integers wider than 64 bits are decoded as decimal strings, and bytes as base64 strings.
+    pub fn decode(&self, buf: &mut &[u8]) -> Result<JsonValue, ContractError> {
+        Ok(match self {
+            ObiType::U8 => number(u8::from_be_bytes(take(buf)?)),
+            ObiType::U16 => number(u16::from_be_bytes(take(buf)?)),
+            ObiType::U32 => number(u32::from_be_bytes(take(buf)?)),
+            ObiType::U64 => number(u64::from_be_bytes(take(buf)?)),
+            ObiType::U128 => JsonValue::String(u128::from_be_bytes(take(buf)?).to_string()),
+            ObiType::U256 => JsonValue::String(Uint256::from_be_bytes(take(buf)?).to_string()),
+            ObiType::I8 => number(i8::from_be_bytes(take(buf)?)),
+            ObiType::I16 => number(i16::from_be_bytes(take(buf)?)),
+            ObiType::I32 => number(i32::from_be_bytes(take(buf)?)),
+            ObiType::I64 => number(i64::from_be_bytes(take(buf)?)),
+            ObiType::Bool => match take::<1>(buf)? {
+                [0] => JsonValue::Bool(false),
+                [1] => JsonValue::Bool(true),
+                [other] => {
+                    return Err(ContractError::ObiDecode {
+                        reason: format!("invalid bool value {}", other),
+                    })
+                }
+            },
+            ObiType::String => {
+                let bytes = take_bytes(buf)?;
+                JsonValue::String(String::from_utf8(bytes).map_err(|_| {
+                    ContractError::ObiDecode {
+                        reason: "invalid UTF-8 in string".to_string(),
+                    }
+                })?)
+            }
+            ObiType::Bytes => JsonValue::String(b64encode(take_bytes(buf)?)),
+            ObiType::Vector(item) => {
+                let len = u32::from_be_bytes(take(buf)?);
+                // Items of empty structs take no input, so the length alone must not drive allocation
+                if len as usize > buf.len() {
+                    return Err(ContractError::ObiDecode {
+                        reason: format!("vector length {} exceeds the remaining input", len),
+                    });
+                }
+                let mut items = vec![];
+                for _ in 0..len {
+                    items.push(item.decode(buf)?);
+                }
+                JsonValue::Array(items)
+            }
+            ObiType::Struct(fields) => JsonValue::Object(
+                fields
+                    .iter()
+                    .map(|(name, field)| Ok((name.clone(), field.decode(buf)?)))
+                    .collect::<Result<Vec<_>, ContractError>>()?,
+            ),
+        })
+    }
+}
+
+fn parse_int<T: FromStr>(value: &JsonValue) -> Result<T, ContractError> {
+    let digits = match value {
+        JsonValue::Number(digits) | JsonValue::String(digits) => digits,
+        _ => {
+            return Err(ContractError::ObiEncode {
+                reason: format!("expected integer, got {:?}", value),
+            })
+        }
+    };
+
+    digits.parse::<T>().map_err(|_| ContractError::ObiEncode {
+        reason: format!("integer {} is out of range", digits),
+    })
+}
+
+fn number<T: ToString>(value: T) -> JsonValue {
+    JsonValue::Number(value.to_string())
+}
+
+fn encode_len(len: usize, out: &mut Vec<u8>) -> Result<(), ContractError> {
+    let len = u32::try_from(len).map_err(|_| ContractError::ObiEncode {
+        reason: "length does not fit in u32".to_string(),
+    })?;
+    out.extend_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) -> Result<(), ContractError> {
+    encode_len(bytes.len(), out)?;
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+fn take<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N], ContractError> {
+    if buf.len() < N {
+        return Err(ContractError::ObiDecode {
+            reason: "unexpected end of input".to_string(),
+        });
+    }
+    let (head, rest) = buf.split_at(N);
+    *buf = rest;
+
+    let mut bytes = [0u8; N];
+    bytes.copy_from_slice(head);
+    Ok(bytes)
+}
+
+fn take_bytes(buf: &mut &[u8]) -> Result<Vec<u8>, ContractError> {
+    let len = u32::from_be_bytes(take(buf)?) as usize;
+    if buf.len() < len {
+        return Err(ContractError::ObiDecode {
+            reason: "unexpected end of input".to_string(),
+        });
+    }
+    let (head, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(head.to_vec())
 }
 
 #[cfg(test)]
@@ -41,6 +522,108 @@ mod test {
         println!("{:?}", res);
     }
 
+    #[test]
+    fn decode_malformed_test() {
+        assert!(matches!(
+            PriceDataOutput::decode_obi("not base64!"),
+            Err(ContractError::InvalidBase64 { .. })
+        ));
+        assert!(matches!(
+            PriceDataOutput::decode_obi("AAAAAQAA"),
+            Err(ContractError::ObiDecode { .. })
+        ));
+        assert!(matches!(
+            PriceDataOutput::decode_obi("AAAAAQAAAAAAHxieAA=="),
+            Err(ContractError::TrailingBytes { count: 1 })
+        ));
+    }
+
+    #[test]
+    fn vrf_test() {
+        let input = VrfInput {
+            seed: vec![1, 2],
+            time: 3,
+            worker_contract: "a".to_string(),
+        }
+        .encode_obi()
+        .unwrap();
+        assert_eq!(
+            input,
+            vec![0, 0, 0, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 1, b'a']
+        );
+
+        let VrfOutput { proof, result } = VrfOutput::decode_obi("AAAAAgECAAAAAQM=").unwrap();
+        assert_eq!(proof, vec![1, 2]);
+        assert_eq!(result, vec![3]);
+    }
+
+    #[test]
+    fn calldata_variant_test() {
+        let variant = CalldataVariant::SymbolsMinimumSourceCount {
+            minimum_source_count: 3,
+        };
+        let calldata = variant.encode_obi(vec!["A".to_string()], 1000000).unwrap();
+        assert_eq!(calldata, vec![0, 0, 0, 1, 0, 0, 0, 1, b'A', 3]);
+
+        let symbols = vec!["C".to_string(), "B".to_string(), "A".to_string()];
+        let rates = variant
+            .decode_rates(&symbols, "AAAAAgAAAAFBAAAAAAAAAAAFAAAAAUIBAAAAAAAAAAA=")
+            .unwrap();
+        assert_eq!(rates, vec![None, None, Some(5)]);
+
+        let rates = CalldataVariant::SymbolsMultiplier
+            .decode_rates(&symbols[..1], "AAAAAQAAAAAAHxie")
+            .unwrap();
+        assert_eq!(rates, vec![Some(2037918)]);
+    }
+
+    #[test]
+    fn schema_test() {
+        let schema = ObiSchema::parse("{symbols:[string],multiplier:u64}/{rates:[u64]}").unwrap();
+
+        let input = JsonValue::from_slice(br#"{"symbols":["LUNA"],"multiplier":1000000}"#).unwrap();
+        let expected = PriceDataInput {
+            symbol: vec!["LUNA".to_string()],
+            multiplier: 1000000,
+        }
+        .encode_obi()
+        .unwrap();
+        assert_eq!(schema.encode_input(&input).unwrap(), expected);
+
+        let output = schema.decode_output("AAAAAQAAAAAAHxie").unwrap();
+        assert_eq!(output.to_vec(), br#"{"rates":[2037918]}"#.to_vec());
+
+        let nested =
+            ObiType::parse("{ a: i8, b: {c: u256, d: bytes, e: bool}, f: [[u128]] }").unwrap();
+        let value = JsonValue::from_slice(
+            br#"{"a":-5,"b":{"c":"1000000000000000000000000000000","d":"AQI=","e":true},"f":[["7"]]}"#,
+        )
+        .unwrap();
+        let mut encoded = vec![];
+        nested.encode(&value, &mut encoded).unwrap();
+        assert_eq!(nested.decode(&mut encoded.as_slice()).unwrap(), value);
+    }
+
+    #[test]
+    fn decode_limits_test() {
+        let empty_items = ObiType::parse("[{}]").unwrap();
+        assert!(matches!(
+            empty_items.decode(&mut [0xff, 0xff, 0xff, 0xff].as_slice()),
+            Err(ContractError::ObiDecode { .. })
+        ));
+        assert_eq!(
+            empty_items.decode(&mut [0, 0, 0, 0].as_slice()).unwrap(),
+            JsonValue::Array(vec![])
+        );
+
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(JsonValue::from_slice(nested(64).as_bytes()).is_ok());
+        assert!(matches!(
+            JsonValue::from_slice(nested(100_000).as_bytes()),
+            Err(ContractError::InvalidJson { .. })
+        ));
+    }
+
     #[test]
     fn encode_test() {
         let data = PriceDataInput {
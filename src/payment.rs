@@ -0,0 +1,96 @@
+use cosmwasm_std::{to_binary, Addr, BankMsg, CosmosMsg, MessageInfo, StdResult, Storage, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+
+use crate::error::ContractError;
+use crate::state::{Payment, CW20_TREASURY, TREASURY};
+
+/// ## Description
+/// Rejects funds attached to a message that neither escrows nor collects them,
+/// since they could never be withdrawn from the contract afterwards.
+///
+/// ## Params
+/// - **info** is an object of type [`MessageInfo`].
+pub fn nonpayable(info: &MessageInfo) -> Result<(), ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::NonPayable {});
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Builds the message returning an escrowed payment to the specified address.
+///
+/// ## Params
+/// - **payment** is an object of type [`Payment`] which is the escrowed payment.
+///
+/// - **recipient** is an object of type [`Addr`] which is the address to return the payment to.
+pub fn refund_msg(payment: Payment, recipient: &Addr) -> StdResult<CosmosMsg> {
+    Ok(match payment {
+        Payment::Native(coin) => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![coin],
+        }
+        .into(),
+        Payment::Cw20(coin) => WasmMsg::Execute {
+            contract_addr: coin.address.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: coin.amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+/// ## Description
+/// Adds an escrowed payment to the fees collected by the contract.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **payment** is an object of type [`Payment`] which is the escrowed payment.
+pub fn collect_payment(storage: &mut dyn Storage, payment: Payment) -> StdResult<()> {
+    match payment {
+        Payment::Native(coin) => {
+            TREASURY.update(storage, &coin.denom, |collected| -> StdResult<_> {
+                Ok(collected.unwrap_or_default().checked_add(coin.amount)?)
+            })?;
+        }
+        Payment::Cw20(coin) => {
+            CW20_TREASURY.update(storage, &coin.address, |collected| -> StdResult<_> {
+                Ok(collected.unwrap_or_default().checked_add(coin.amount)?)
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Settles the payment escrowed for a request once it is finished. The payment is collected
+/// into the treasury if the request succeeded, and refunded to the requester otherwise.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **payment** is an [`Option`] of type [`Payment`] which is the escrowed payment, if any.
+///
+/// - **requester** is an object of type [`Addr`] which is the address that paid for the request.
+///
+/// - **succeeded** is whether the request resolved into usable data.
+pub fn settle_payment(
+    storage: &mut dyn Storage,
+    payment: Option<Payment>,
+    requester: &Addr,
+    succeeded: bool,
+) -> StdResult<Vec<CosmosMsg>> {
+    match payment {
+        Some(payment) if succeeded => {
+            collect_payment(storage, payment)?;
+            Ok(vec![])
+        }
+        Some(payment) => Ok(vec![refund_msg(payment, requester)?]),
+        None => Ok(vec![]),
+    }
+}
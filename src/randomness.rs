@@ -0,0 +1,119 @@
+use cosmwasm_std::{attr, Binary, IbcReceiveResponse, StdResult, Storage, SubMsg};
+
+use crate::error::ContractError;
+use crate::ibc::{
+    fail_packet_receive, make_ack_success, parse_number, requester_callback, AckErrorCode,
+};
+use crate::msg::RequesterExecuteMsg;
+use crate::obi::VrfOutput;
+use crate::state::{Randomness, RandomnessStatus, RANDOMNESS};
+
+/// ## Description
+/// Stores the verifiable randomness resolved for a randomness request and delivers it back to the
+/// contract that requested it. The request is marked as failed and a failure callback is sent
+/// instead if it did not resolve into a usable result.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **client_id** is the unique identifier of the oracle request.
+///
+/// - **request** is an object of type [`Randomness`] which is the pending request being resolved.
+///
+/// - **request_id** is the BandChain request ID associated with the response.
+///
+/// - **resolve_time** is the time the request was resolved on BandChain.
+///
+/// - **resolve_status** is the status of the request on BandChain.
+///
+/// - **result** is the OBI-encoded result of the request.
+pub fn execute_randomness_update(
+    storage: &mut dyn Storage,
+    client_id: String,
+    mut request: Randomness,
+    request_id: String,
+    resolve_time: String,
+    resolve_status: String,
+    result: String,
+) -> Result<IbcReceiveResponse, ContractError> {
+    if resolve_status != *"RESOLVE_STATUS_SUCCESS" {
+        let error = "Band request did not resolve successfully";
+        let callback = fail_randomness_request(storage, client_id, request, error.to_string())?;
+        return Ok(
+            fail_packet_receive(AckErrorCode::UnresolvedStatus, error)?.add_submessage(callback)
+        );
+    }
+
+    let decoded = parse_number("request_id", &request_id).and_then(|request_id| {
+        let resolve_time = parse_number("resolve_time", &resolve_time)?;
+        Ok((request_id, resolve_time, VrfOutput::decode_obi(&result)?))
+    });
+    let (bandchain_request_id, bandchain_resolve_time, VrfOutput { proof, result }) = match decoded
+    {
+        Ok(decoded) => decoded,
+        Err(error) => {
+            let callback = fail_randomness_request(storage, client_id, request, error.to_string())?;
+            return Ok(
+                fail_packet_receive(AckErrorCode::from(&error), &error.to_string())?
+                    .add_submessage(callback),
+            );
+        }
+    };
+
+    let hash = Binary(result);
+    let proof = Binary(proof);
+    request.status = RandomnessStatus::Fulfilled {
+        hash: hash.clone(),
+        proof: proof.clone(),
+        bandchain_request_id,
+        bandchain_resolve_time,
+    };
+    RANDOMNESS.save(storage, &client_id, &request)?;
+
+    Ok(IbcReceiveResponse::new()
+        .add_submessage(requester_callback(
+            &request.requester,
+            &RequesterExecuteMsg::ReceiveRandomness {
+                request_id: client_id.clone(),
+                hash,
+                proof,
+            },
+        )?)
+        .add_attributes(vec![
+            attr("method", "execute_randomness_update"),
+            attr("client_id", client_id),
+            attr("requester", request.requester),
+        ])
+        .set_ack(make_ack_success()))
+}
+
+/// ## Description
+/// Marks a pending randomness request as failed and returns the callback notifying its requester.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **client_id** is the unique identifier of the oracle request.
+///
+/// - **request** is an object of type [`Randomness`] which is the pending request that failed.
+///
+/// - **error** is the reason the request failed.
+pub fn fail_randomness_request(
+    storage: &mut dyn Storage,
+    client_id: String,
+    mut request: Randomness,
+    error: String,
+) -> StdResult<SubMsg> {
+    request.status = RandomnessStatus::Failed {
+        error: error.clone(),
+    };
+    RANDOMNESS.save(storage, &client_id, &request)?;
+
+    requester_callback(
+        &request.requester,
+        &RequesterExecuteMsg::ReceiveRandomnessFailure {
+            request_id: client_id,
+            error,
+        },
+    )
+}
@@ -0,0 +1,39 @@
+use cosmwasm_std::{Addr, StdResult, Storage};
+
+use crate::state::{KeeperRewards, CONFIG, KEEPER_REWARDS, REWARD_POOL};
+
+/// ## Description
+/// Credits the configured keeper reward to the address that triggered a successful job update.
+/// Nothing is credited if no reward is configured or the reward pool cannot cover it.
+///
+/// ## Params
+/// - **storage** is an object of type [`Storage`].
+///
+/// - **keeper** is an object of type [`Addr`] which is the address that triggered the request.
+pub fn reward_keeper(storage: &mut dyn Storage, keeper: &Addr) -> StdResult<()> {
+    let reward = match CONFIG.load(storage)?.keeper_reward {
+        Some(reward) => reward,
+        None => return Ok(()),
+    };
+
+    let pool = REWARD_POOL
+        .may_load(storage, &reward.denom)?
+        .unwrap_or_default();
+    if pool < reward.amount {
+        return Ok(());
+    }
+    REWARD_POOL.save(storage, &reward.denom, &(pool - reward.amount))?;
+
+    KEEPER_REWARDS.update(
+        storage,
+        (keeper, &reward.denom),
+        |rewards| -> StdResult<_> {
+            let mut rewards: KeeperRewards = rewards.unwrap_or_default();
+            rewards.claimable = rewards.claimable.checked_add(reward.amount)?;
+            rewards.total_earned = rewards.total_earned.checked_add(reward.amount)?;
+            Ok(rewards)
+        },
+    )?;
+
+    Ok(())
+}
@@ -1,13 +1,42 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Decimal};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Empty, Uint128};
+use cw20::Cw20CoinVerified;
 use cw_storage_plus::{Item, Map};
 
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const JOB_COUNT: Item<u64> = Item::new("job_count");
+pub const REQUEST_COUNT: Item<u64> = Item::new("request_count");
 pub const JOBS: Map<&str, Job> = Map::new("job"); // job_id -> Job {}
+pub const JOB_STATUS: Map<&str, JobStatus> = Map::new("job_status"); // job_id -> JobStatus {}
+pub const DUE_JOBS: Map<(u64, &str), Empty> = Map::new("due_jobs"); // (due_at, job_id) -> Empty {}
 pub const PRICES: Map<&str, PriceData> = Map::new("prices");
+pub const SOURCE_PRICES: Map<(&str, &str), PriceData> = Map::new("source_prices"); // (symbol, job_id) -> PriceData {}
+pub const AGGREGATIONS: Map<&str, Aggregation> = Map::new("aggregations"); // symbol -> Aggregation {}
+pub const PRICE_SOURCES: Map<&str, Vec<PriceSource>> = Map::new("price_sources"); // symbol -> [PriceSource {}]
+pub const PRICE_OVERRIDES: Map<&str, PriceOverride> = Map::new("price_overrides"); // symbol -> PriceOverride {}
+pub const SUBSCRIPTIONS: Map<(&str, &Addr), Empty> = Map::new("subscriptions"); // (symbol, subscriber) -> Empty {}
+pub const ALLOWED_REQUESTERS: Map<&Addr, Empty> = Map::new("allowed_requesters"); // requester -> Empty {}
+pub const ON_DEMAND_REQUESTS: Map<&str, OnDemandRequest> = Map::new("on_demand_requests"); // client_id -> OnDemandRequest {}
+pub const PENDING_REQUESTS: Map<&str, PendingRequest> = Map::new("pending_requests"); // job_id -> PendingRequest {}
+pub const ALLOWED_SYMBOLS: Map<&str, Empty> = Map::new("allowed_symbols"); // symbol -> Empty {}
+pub const SYMBOL_REQUESTS: Map<&str, SymbolRequest> = Map::new("symbol_requests"); // client_id -> SymbolRequest {}
+pub const SYMBOL_LAST_REQUEST: Map<&str, u64> = Map::new("symbol_last_request"); // symbol -> time of the last symbol request
+pub const JOB_HISTORY: Map<(&str, u64), JobHistoryEntry> = Map::new("job_history"); // (job_id, sequence) -> JobHistoryEntry {}
+pub const JOB_STATS: Map<&str, JobStats> = Map::new("job_stats"); // job_id -> JobStats {}
+pub const BANDCHAIN_REQUESTS: Map<u64, BandchainRequest> = Map::new("bandchain_requests"); // bandchain_request_id -> BandchainRequest {}
+pub const SYMBOL_FAILURES: Map<&str, SymbolFailures> = Map::new("symbol_failures"); // symbol -> SymbolFailures {}
+pub const RANDOMNESS: Map<&str, Randomness> = Map::new("randomness"); // client_id -> Randomness {}
+pub const JOB_RESULTS: Map<&str, JobResult> = Map::new("job_results"); // job_id -> JobResult {}
+pub const RAW_RESULTS: Map<(&str, u64), RawResult> = Map::new("raw_results"); // (job_id, bandchain_request_id) -> RawResult {}
+pub const ORACLE_SCRIPTS: Map<u64, OracleScript> = Map::new("oracle_scripts"); // oracle_script_id -> OracleScript {}
+pub const BATCH_REQUESTS: Map<&str, BatchRequest> = Map::new("batch_requests"); // client_id -> BatchRequest {}
+pub const TREASURY: Map<&str, Uint128> = Map::new("treasury"); // denom -> collected fees
+pub const CW20_UPDATE_PRICES: Map<&Addr, Uint128> = Map::new("cw20_update_prices"); // cw20 token -> price per request
+pub const CW20_TREASURY: Map<&Addr, Uint128> = Map::new("cw20_treasury"); // cw20 token -> collected fees
+pub const REWARD_POOL: Map<&str, Uint128> = Map::new("reward_pool"); // denom -> funded rewards
+pub const KEEPER_REWARDS: Map<(&Addr, &str), KeeperRewards> = Map::new("keeper_rewards"); // (keeper, denom) -> KeeperRewards {}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -15,6 +44,21 @@ pub struct Config {
     pub owner: Addr,
     /// The channel name to use for the oracle requests
     pub channel: String,
+    /// The reward paid from the reward pool to the address that triggered a successful update of a due job
+    #[serde(default)]
+    pub keeper_reward: Option<Coin>,
+    /// The maximum number of due jobs requested by each chain tick
+    #[serde(default)]
+    pub tick_budget: Option<u32>,
+    /// The request parameters used to update symbols that have no registered job
+    #[serde(default)]
+    pub default_request: Option<DefaultRequest>,
+    /// The number of raw results kept for each job
+    #[serde(default)]
+    pub result_retention: Option<u32>,
+    /// The request parameters used to request randomness from the VRF oracle script
+    #[serde(default)]
+    pub vrf_request: Option<VrfRequest>,
 }
 
 /// ## Description
@@ -25,6 +69,42 @@ pub struct ConfigResponse {
     pub owner: String,
     /// The channel name to use for the oracle requests
     pub channel: String,
+    /// The reward paid from the reward pool to the address that triggered a successful update of a due job
+    pub keeper_reward: Option<Coin>,
+    /// The maximum number of due jobs requested by each chain tick
+    pub tick_budget: u32,
+    /// The request parameters used to update symbols that have no registered job
+    pub default_request: Option<DefaultRequest>,
+    /// The number of raw results kept for each job
+    pub result_retention: u32,
+    /// The request parameters used to request randomness from the VRF oracle script
+    pub vrf_request: Option<VrfRequest>,
+}
+
+/// ## Description
+/// This structure holds the request parameters used to update symbols that have no registered job.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DefaultRequest {
+    /// The oracle script ID to query the data from
+    pub oracle_script_id: u64,
+    /// The multiplier value used to multiply the price data value by (to preserve precision)
+    pub multiplier: u64,
+    /// The number of validators that are requested to respond to this request
+    pub ask_count: u64,
+    /// The minimum number of validators necessary for the request to proceed to the execution phase
+    pub min_count: u64,
+}
+
+/// ## Description
+/// This structure holds the request parameters used to request randomness from the VRF oracle script.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VrfRequest {
+    /// The ID of the VRF oracle script on BandChain
+    pub oracle_script_id: u64,
+    /// The number of validators that are requested to respond to this request
+    pub ask_count: u64,
+    /// The minimum number of validators necessary for the request to proceed to the execution phase
+    pub min_count: u64,
 }
 
 /// ## Description
@@ -33,16 +113,77 @@ pub struct ConfigResponse {
 pub struct Job {
     /// The ID of the oracle script on BandChain to request the price data from
     pub oracle_script_id: u64,
-    /// The list of asset symbols to request the price data for.
+    /// The list of asset symbols to request the price data for. Generic jobs have none.
     pub symbols: Vec<String>,
-    /// The multiplier value used to multiply the price data value by (to preserve precision)
-    pub multiplier: u64,
+    /// The multiplier value used to multiply the price data value by (to preserve precision).
+    /// Generic jobs have none.
+    pub multiplier: Option<u64>,
     /// The OBI-encoded calldata bytes available for oracle executor to read.
     pub calldata: Vec<u8>,
     /// The number of validators that are requested to respond to this oracle request. Higher value means more security, at a higher gas cost.
     pub ask_count: u64,
     /// The minimum number of validators necessary for the request to proceed to the execution phase. Higher value means more security, at the cost of liveness.
     pub min_count: u64,
+    /// The funds a caller must attach to request an update of this job, if any.
+    #[serde(default)]
+    pub update_price: Option<Coin>,
+    /// The target interval (in seconds) between successful updates of this job, if it is scheduled.
+    #[serde(default)]
+    pub update_interval: Option<u64>,
+    /// The IDs of the jobs requested once an update of this job succeeds.
+    #[serde(default)]
+    pub follow_ups: Vec<String>,
+    /// The OBI schema of the oracle script of a generic job, whose results are stored as JSON
+    /// instead of prices. Price jobs have none.
+    #[serde(default)]
+    pub schema: Option<String>,
+    /// The calldata layout expected by the oracle script of a price job
+    #[serde(default)]
+    pub calldata_variant: CalldataVariant,
+}
+
+/// The fixed multiplier of the rates returned by the BandChain standard dataset
+pub const STANDARD_DATASET_MULTIPLIER: u64 = 1_000_000_000;
+
+impl Job {
+    /// Returns the multiplier the rate of the symbol at the specified index was multiplied by,
+    /// if the job requests prices.
+    pub fn multiplier_of(&self, index: usize) -> Option<u64> {
+        match &self.calldata_variant {
+            CalldataVariant::SymbolsMultiplier => self.multiplier,
+            CalldataVariant::SymbolsMinimumSourceCount { .. } => Some(STANDARD_DATASET_MULTIPLIER),
+            CalldataVariant::PerSymbolMultipliers { multipliers } => {
+                multipliers.get(index).copied()
+            }
+        }
+    }
+}
+
+/// ## Description
+/// This enum describes the calldata layouts of the BandChain standard price oracle scripts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CalldataVariant {
+    /// `{symbols:[string],multiplier:u64}/{rates:[u64]}`
+    SymbolsMultiplier,
+    /// `{symbols:[string],minimum_source_count:u8}/{responses:[{symbol:string,response_code:u8,rate:u64}]}`,
+    /// where symbols with a non-zero response code are unavailable and rates are multiplied by
+    /// [`STANDARD_DATASET_MULTIPLIER`]
+    SymbolsMinimumSourceCount {
+        /// The minimum number of data sources that must report a symbol
+        minimum_source_count: u8,
+    },
+    /// `{symbols:[string],multipliers:[u64]}/{rates:[u64]}`
+    PerSymbolMultipliers {
+        /// The multiplier of each symbol, in order
+        multipliers: Vec<u64>,
+    },
+}
+
+impl Default for CalldataVariant {
+    fn default() -> Self {
+        CalldataVariant::SymbolsMultiplier
+    }
 }
 
 /// ## Description
@@ -56,3 +197,451 @@ pub struct PriceData {
     /// The time the request for this price data was resolved on BandChain.
     pub bandchain_resolve_time: u64,
 }
+
+/// ## Description
+/// This enum describes the available methods for combining the prices reported by several jobs for the same symbol.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationMethod {
+    /// Publish the median of all fresh source prices.
+    Median,
+    /// Publish the arithmetic mean of all fresh source prices.
+    Mean,
+    /// Publish the price of the primary job while it is fresh, otherwise the most recently resolved fresh source.
+    PrimaryWithFallback {
+        /// The ID of the job whose price is preferred.
+        primary_job_id: String,
+    },
+}
+
+/// ## Description
+/// This structure holds the aggregation policy of a symbol that is covered by multiple jobs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Aggregation {
+    /// The method used to combine the source prices.
+    pub method: AggregationMethod,
+    /// The minimum number of fresh sources required to publish a new price.
+    pub min_sources: u64,
+    /// The maximum age (in seconds since BandChain resolve time) of a source price for it to be considered fresh.
+    pub max_age: u64,
+}
+
+/// ## Description
+/// This structure describes the latest price reported for a symbol by a single job.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SourcePriceResponse {
+    /// The ID of the job that reported the price.
+    pub job_id: String,
+    /// The price data reported by the job.
+    pub price: PriceData,
+}
+
+/// ## Description
+/// This structure describes a job that can serve the price of a symbol, in order of preference.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceSource {
+    /// The ID of the job reporting the price.
+    pub job_id: String,
+    /// The maximum age (in seconds since BandChain resolve time) of the job's price for it to be served.
+    pub heartbeat: u64,
+}
+
+/// ## Description
+/// This structure is used to return the latest price of an asset along with where it came from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceResponse {
+    /// The latest price data for the requested asset
+    pub rate: Decimal,
+    /// The BandChain request ID associated with this price data.
+    pub bandchain_request_id: u64,
+    /// The time the request for this price data was resolved on BandChain.
+    pub bandchain_resolve_time: u64,
+    /// The ID of the job the price was served from, if the symbol has price sources configured.
+    pub job_id: Option<String>,
+    /// The ID of the oracle script the price was served from, if the symbol has price sources configured.
+    pub oracle_script_id: Option<u64>,
+    /// The expiry time of the owner override shadowing the oracle price, if one is active.
+    pub override_expires_at: Option<u64>,
+}
+
+/// ## Description
+/// This structure holds a price pinned by the owner, shadowing the oracle price until it expires.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceOverride {
+    /// The pinned price of the asset
+    pub rate: Decimal,
+    /// The UNIX epoch time (in seconds) at which the override stops being served.
+    pub expires_at: u64,
+}
+
+/// ## Description
+/// This structure holds an in-flight one-off price request made by a consumer contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OnDemandRequest {
+    /// The address of the contract the result is delivered to
+    pub requester: Addr,
+    /// The list of asset symbols requested.
+    pub symbols: Vec<String>,
+    /// The multiplier value used to multiply the price data value by (to preserve precision)
+    pub multiplier: u64,
+    /// The funds escrowed for the request, refunded to the requester if it fails
+    #[serde(default)]
+    pub payment: Option<Payment>,
+}
+
+/// ## Description
+/// This structure holds an in-flight oracle request for symbols that have no registered job.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SymbolRequest {
+    /// The list of asset symbols requested.
+    pub symbols: Vec<String>,
+    /// The multiplier value used to multiply the price data value by (to preserve precision)
+    pub multiplier: u64,
+}
+
+/// ## Description
+/// This structure holds an in-flight oracle request merging the symbols of several compatible jobs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchRequest {
+    /// The IDs of the jobs merged into the request
+    pub job_ids: Vec<String>,
+    /// The union of the jobs' symbols, in the order they were requested
+    pub symbols: Vec<String>,
+}
+
+/// ## Description
+/// This structure holds an in-flight oracle request sent for a job.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRequest {
+    /// The address that triggered the request
+    pub requester: Addr,
+    /// The funds escrowed for the request, refunded to the requester if it fails
+    pub payment: Option<Payment>,
+    /// The block time (in seconds) at which the request was sent
+    pub sent_at: u64,
+    /// The client ID the request was sent with. Packets carrying any other client ID belong to a replaced request
+    pub client_id: String,
+    /// Whether the requester earns the keeper reward once the request succeeds
+    #[serde(default)]
+    pub rewarded: bool,
+    /// The number of follow-ups that led to the request, 0 for a request made directly
+    #[serde(default)]
+    pub depth: u32,
+}
+
+/// ## Description
+/// This enum describes the funds attached by a caller to pay for an oracle request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Payment {
+    /// A native coin attached to the request message.
+    Native(Coin),
+    /// A cw20 token sent to the contract along with the request.
+    Cw20(Cw20CoinVerified),
+}
+
+/// ## Description
+/// This structure is used to return the update fees collected by the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TreasuryResponse {
+    /// The native fees collected
+    pub native: Vec<Coin>,
+    /// The cw20 fees collected
+    pub cw20: Vec<Cw20CoinVerified>,
+}
+
+/// ## Description
+/// This structure holds the rewards earned by a keeper in a single denom.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct KeeperRewards {
+    /// The rewards earned but not claimed yet
+    pub claimable: Uint128,
+    /// The total rewards ever earned
+    pub total_earned: Uint128,
+}
+
+/// ## Description
+/// This structure is used to return the rewards earned by a keeper in a single denom.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KeeperRewardsResponse {
+    /// The denom of the rewards
+    pub denom: String,
+    /// The rewards earned but not claimed yet
+    pub claimable: Uint128,
+    /// The total rewards ever earned
+    pub total_earned: Uint128,
+}
+
+/// ## Description
+/// This structure holds the request and success timestamps of a job.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct JobStatus {
+    /// The block time (in seconds) at which the last request for the job was sent
+    pub last_request: Option<u64>,
+    /// The block time (in seconds) at which the last successful response for the job was received
+    pub last_success: Option<u64>,
+}
+
+/// ## Description
+/// This structure is used to return a job that is due for an update.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DueJobResponse {
+    /// The ID of the job
+    pub job_id: String,
+    /// The number of seconds elapsed since the job became due
+    pub overdue_by: u64,
+}
+
+/// ## Description
+/// This enum describes how an oracle request sent for a job ended.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestOutcome {
+    /// The request resolved and its result was stored
+    Success,
+    /// The request resolved on BandChain without a usable result
+    Failure,
+    /// The request expired on BandChain before enough validators reported
+    Expired,
+    /// The request packet timed out before reaching BandChain
+    Timeout,
+    /// The request packet was rejected by BandChain
+    Rejected,
+    /// The request never came back and was replaced by a new one
+    Replaced,
+}
+
+/// ## Description
+/// This structure holds a finished oracle request of a job.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JobHistoryEntry {
+    /// The position of the request in the job's history
+    pub sequence: u64,
+    /// The block time (in seconds) at which the request was sent
+    pub sent_at: u64,
+    /// The BandChain request ID, if the request reached BandChain
+    pub bandchain_request_id: Option<u64>,
+    /// How the request ended
+    pub outcome: RequestOutcome,
+    /// The time (in seconds) between sending the request and its resolution on BandChain,
+    /// or its outcome if it never resolved
+    pub latency: u64,
+}
+
+/// ## Description
+/// This structure holds the aggregate request counters of a job.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct JobStats {
+    /// The number of requests sent
+    pub requests: u64,
+    /// The number of requests that resolved successfully
+    pub successes: u64,
+    /// The number of requests that resolved without a usable result
+    pub failures: u64,
+    /// The number of requests that expired on BandChain
+    pub expired: u64,
+    /// The number of requests that timed out
+    pub timeouts: u64,
+    /// The number of requests rejected by BandChain
+    pub rejected: u64,
+    /// The number of requests that never came back and were replaced
+    pub replaced: u64,
+    /// The summed latency (in seconds) of the finished requests
+    pub total_latency: u64,
+}
+
+impl JobStats {
+    /// Returns the number of finished requests.
+    pub fn finished(&self) -> u64 {
+        self.successes
+            + self.failures
+            + self.expired
+            + self.timeouts
+            + self.rejected
+            + self.replaced
+    }
+}
+
+/// ## Description
+/// This structure describes the request statistics of a job.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JobStatsResponse {
+    /// The aggregate request counters of the job
+    pub stats: JobStats,
+    /// The average latency (in seconds) of the finished requests
+    pub average_latency: u64,
+}
+
+/// ## Description
+/// This structure holds the oracle response received for a BandChain request.
+/// Only the responses some stored price still comes from are kept.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BandchainRequest {
+    /// The unique identifier the request was sent with
+    pub client_id: String,
+    /// The IDs of the jobs the request was sent for, empty if it was not sent for a job
+    pub job_ids: Vec<String>,
+    /// The symbols the request was sent for
+    pub symbols: Vec<String>,
+    /// The channel the response was received on
+    pub channel: String,
+    /// The sequence of the response packet on the channel
+    pub packet_sequence: u64,
+    /// The status of the request on BandChain
+    pub resolve_status: String,
+    /// The UNIX epoch time at which the request was resolved
+    pub resolve_time: String,
+    /// The OBI-encoded result of the request, as received
+    pub result: String,
+}
+
+/// ## Description
+/// This structure describes the source price of a symbol along with the request that produced it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SourceProvenance {
+    /// The ID of the source that reported the price, which is a job ID for registered jobs
+    pub source: String,
+    /// The price reported by the source
+    pub price: PriceData,
+    /// The BandChain request that produced the price, if it is known
+    pub request: Option<BandchainRequest>,
+}
+
+/// ## Description
+/// This structure describes everything needed to cross-check the current price of a symbol against BandChain.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProvenanceResponse {
+    /// The current price of the symbol
+    pub price: PriceResponse,
+    /// The BandChain request that produced the current price, if it is known
+    pub request: Option<BandchainRequest>,
+    /// The prices reported by each source for the symbol
+    pub sources: Vec<SourceProvenance>,
+}
+
+/// ## Description
+/// This structure holds the raw result of a successful request of a job, kept to re-derive its prices.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RawResult {
+    /// The symbols the result holds rates for, in order
+    pub symbols: Vec<String>,
+    /// The OBI-encoded result, as received
+    pub result: String,
+    /// The time the request was resolved on BandChain
+    pub resolve_time: u64,
+}
+
+/// ## Description
+/// This structure describes a raw result of a job along with the BandChain request that produced it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RawResultResponse {
+    /// The BandChain request ID that produced the result
+    pub request_id: u64,
+    /// The raw result
+    pub result: RawResult,
+}
+
+/// ## Description
+/// This enum describes what an oracle script is used for.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleScriptKind {
+    /// The script returns the prices of the requested symbols
+    PriceFeed,
+    /// The script returns arbitrary data described by its schema
+    Generic,
+}
+
+/// ## Description
+/// This structure holds a registered BandChain oracle script.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OracleScript {
+    /// The OBI schema of the script, e.g. `{symbols:[string],multiplier:u64}/{rates:[u64]}`
+    pub schema: String,
+    /// What the script is used for
+    pub kind: OracleScriptKind,
+    /// The default number of validators that are requested to respond to a request
+    pub ask_count: u64,
+    /// The default minimum number of validators necessary for a request to proceed to the execution phase
+    pub min_count: u64,
+    /// The maximum tokens that will be paid to all data source providers
+    pub fee_limit: Vec<Coin>,
+    /// The maximum gas to be used during the prepare phase of a request
+    pub prepare_gas: u64,
+    /// The maximum gas to be used during the execution phase of a request
+    pub execute_gas: u64,
+}
+
+/// ## Description
+/// This structure describes a registered oracle script along with its ID.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OracleScriptResponse {
+    /// The ID of the oracle script on BandChain
+    pub oracle_script_id: u64,
+    /// The oracle script details
+    pub oracle_script: OracleScript,
+}
+
+/// ## Description
+/// This structure holds the latest decoded result of a generic job.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JobResult {
+    /// The result decoded with the job's output schema, as a JSON document
+    pub result: String,
+    /// The BandChain request ID associated with this result
+    pub bandchain_request_id: u64,
+    /// The time the request for this result was resolved on BandChain
+    pub bandchain_resolve_time: u64,
+}
+
+/// ## Description
+/// This structure holds a randomness request made by a consumer contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Randomness {
+    /// The address of the contract the randomness is delivered to
+    pub requester: Addr,
+    /// The seed provided by the requester
+    pub seed: Binary,
+    /// The block time (in seconds) the randomness was requested at
+    pub time: u64,
+    /// Where the request stands
+    pub status: RandomnessStatus,
+}
+
+/// ## Description
+/// This enum describes the state of a randomness request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RandomnessStatus {
+    /// The request has not resolved yet
+    Pending,
+    /// The request resolved into verifiable randomness
+    Fulfilled {
+        /// The random hash produced by the VRF
+        hash: Binary,
+        /// The proof the hash was derived from the seed
+        proof: Binary,
+        /// The BandChain request ID associated with the randomness
+        bandchain_request_id: u64,
+        /// The time the request was resolved on BandChain
+        bandchain_resolve_time: u64,
+    },
+    /// The request failed and will never resolve
+    Failed {
+        /// The reason the request failed
+        error: String,
+    },
+}
+
+/// ## Description
+/// This structure holds the failure counters of a symbol, counting the successful responses that
+/// reported the symbol as unavailable.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct SymbolFailures {
+    /// The number of responses the symbol was unavailable in
+    pub failures: u64,
+    /// The number of responses the symbol was unavailable in since it was last updated
+    pub consecutive_failures: u64,
+    /// The block time (in seconds) the symbol was last unavailable at
+    pub last_failure: Option<u64>,
+}
@@ -0,0 +1,149 @@
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_ibc_packet_recv, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    from_binary, BankMsg, Coin, CosmosMsg, DepsMut, Env, IbcMsg, IbcReceiveResponse, OwnedDeps,
+    Response, SubMsg,
+};
+
+use crate::contract::{execute, instantiate};
+use crate::error::ContractError;
+use crate::ibc::ibc_packet_receive;
+use crate::msg::{ExecuteMsg, InstantiateMsg, OracleRequestPacket, OracleResponsePacket};
+
+pub const CHANNEL: &str = "channel-0";
+
+/// Instantiates the contract owned by `owner` with the oracle channel set.
+pub fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        InstantiateMsg {
+            owner: "owner".to_string(),
+        },
+    )
+    .unwrap();
+    run(
+        deps.as_mut(),
+        "owner",
+        &[],
+        ExecuteMsg::SetChannel {
+            channel: CHANNEL.to_string(),
+        },
+    )
+    .unwrap();
+    deps
+}
+
+pub fn run(
+    deps: DepsMut,
+    sender: &str,
+    funds: &[Coin],
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    execute(deps, mock_env(), mock_info(sender, funds), msg)
+}
+
+/// Registers a price job for `symbols` and returns its ID.
+pub fn register_job(deps: DepsMut, oracle_script_id: u64, symbols: &[&str]) -> String {
+    let res = run(
+        deps,
+        "owner",
+        &[],
+        ExecuteMsg::RegisterJob {
+            oracle_script_id,
+            symbols: symbols.iter().map(|symbol| symbol.to_string()).collect(),
+            multiplier: 1000000,
+            ask_count: Some(4),
+            min_count: Some(3),
+            update_interval: None,
+            calldata_variant: None,
+        },
+    )
+    .unwrap();
+    attribute(&res, "job_id")
+}
+
+pub fn attribute(res: &Response, key: &str) -> String {
+    res.attributes
+        .iter()
+        .find(|attribute| attribute.key == key)
+        .unwrap()
+        .value
+        .clone()
+}
+
+/// Requests an update of the job and returns the client ID the request was sent with.
+pub fn update_job(deps: DepsMut, job_id: &str) -> String {
+    update_job_at(deps, mock_env(), job_id)
+}
+
+/// Requests an update of the job at the block time of `env` and returns the client ID the request was sent with.
+pub fn update_job_at(deps: DepsMut, env: Env, job_id: &str) -> String {
+    let res = execute(
+        deps,
+        env,
+        mock_info("keeper", &[]),
+        ExecuteMsg::UpdateJobData {
+            job_id: job_id.to_string(),
+        },
+    )
+    .unwrap();
+    sent_packets(&res).remove(0).client_id
+}
+
+/// Returns the oracle request packets sent by `res`.
+pub fn sent_packets(res: &Response) -> Vec<OracleRequestPacket> {
+    res.messages
+        .iter()
+        .filter_map(|msg| match &msg.msg {
+            CosmosMsg::Ibc(IbcMsg::SendPacket { data, .. }) => Some(from_binary(data).unwrap()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the recipients and amounts of the bank transfers among `messages`.
+pub fn bank_sends(messages: &[SubMsg]) -> Vec<(String, Vec<Coin>)> {
+    messages
+        .iter()
+        .filter_map(|msg| match &msg.msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                Some((to_address.clone(), amount.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// OBI-encodes `rates` as a price data result.
+pub fn price_result(rates: &[u64]) -> String {
+    let mut encoded = (rates.len() as u32).to_be_bytes().to_vec();
+    for rate in rates {
+        encoded.extend_from_slice(&rate.to_be_bytes());
+    }
+    base64::encode(encoded)
+}
+
+pub fn response_packet(
+    client_id: &str,
+    request_id: u64,
+    resolve_status: &str,
+    result: String,
+) -> OracleResponsePacket {
+    OracleResponsePacket {
+        client_id: client_id.to_string(),
+        request_id: request_id.to_string(),
+        ans_count: "4".to_string(),
+        request_time: mock_env().block.time.seconds().to_string(),
+        resolve_time: mock_env().block.time.seconds().to_string(),
+        resolve_status: resolve_status.to_string(),
+        result,
+    }
+}
+
+pub fn receive(deps: DepsMut, env: Env, packet: &OracleResponsePacket) -> IbcReceiveResponse {
+    ibc_packet_receive(deps, env, mock_ibc_packet_recv(CHANNEL, packet).unwrap()).unwrap()
+}